@@ -0,0 +1,100 @@
+use crate::diagnostics::Diagnostic;
+use crate::playground::{ErrorKind, ModuleSource, Playground};
+use miden_mast_package::{MastArtifact, Package, PackageManifest};
+use miden_vm::{
+    assembly::DefaultSourceManager,
+    utils::{Deserializable, Serializable},
+    Program,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Packages start with this magic string; anything else is read as a serialized [Program].
+const PACKAGE_MAGIC: &[u8] = b"MASP\0";
+const DEFAULT_PACKAGE_NAME: &str = "program";
+
+#[derive(Deserialize)]
+pub struct ExportRequest {
+    pub program: String,
+    #[serde(default)]
+    pub modules: Vec<ModuleSource>,
+    /// Name recorded in the package, `program` by default.
+    pub name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ExportResult {
+    pub success: bool,
+    pub program_hash: Option<String>,
+    /// The serialized program: its MAST forest, entrypoint and kernel.
+    pub mast_bytes: Option<Vec<u8>>,
+    /// The program wrapped in a `.masp` package.
+    pub package_bytes: Option<Vec<u8>>,
+    pub error: Option<String>,
+    pub error_kind: Option<ErrorKind>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub compilation_time_ms: Option<f64>,
+}
+
+/// Wraps a program in a package. Libraries are linked statically, so the package has no
+/// dependencies and runs on any host.
+pub fn package_program(name: &str, program: Program) -> Package {
+    Package {
+        name: name.to_string(),
+        mast: MastArtifact::Executable(Arc::new(program)),
+        manifest: PackageManifest::default(),
+        account_component_metadata_bytes: None,
+    }
+}
+
+/// Reads a program from a `.masp` package or from the bytes of a serialized [Program].
+pub fn decode_program(bytes: &[u8]) -> Result<Program, String> {
+    if !bytes.starts_with(PACKAGE_MAGIC) {
+        return Program::read_from_bytes(bytes).map_err(|e| format!("Invalid MAST: {e}"));
+    }
+
+    let package = Package::read_from_bytes(bytes).map_err(|e| format!("Invalid package: {e}"))?;
+    match package.into_mast_artifact() {
+        MastArtifact::Executable(program) => Ok(Arc::unwrap_or_clone(program)),
+        MastArtifact::Library(_) => {
+            Err("Package contains a library rather than a program".to_string())
+        }
+    }
+}
+
+/// Assembles a program and returns it in both of the forms [decode_program] accepts.
+pub fn export_program(playground: &Playground, request: &ExportRequest) -> ExportResult {
+    let source_manager = Arc::new(DefaultSourceManager::default());
+    let compilation_start = Instant::now();
+    let program = playground.assemble(&source_manager, &request.program, &request.modules);
+    let compilation_time = compilation_start.elapsed().as_millis() as f64;
+
+    let program = match program {
+        Ok(program) => program,
+        Err(e) => {
+            return ExportResult {
+                success: false,
+                program_hash: None,
+                mast_bytes: None,
+                package_bytes: None,
+                error: Some(e.message),
+                error_kind: Some(ErrorKind::Assembly),
+                diagnostics: e.diagnostics,
+                compilation_time_ms: Some(compilation_time),
+            }
+        }
+    };
+
+    let name = request.name.as_deref().unwrap_or(DEFAULT_PACKAGE_NAME);
+    ExportResult {
+        success: true,
+        program_hash: Some(program.hash().to_string()),
+        mast_bytes: Some(program.to_bytes()),
+        package_bytes: Some(package_program(name, program).to_bytes()),
+        error: None,
+        error_kind: None,
+        diagnostics: Vec::new(),
+        compilation_time_ms: Some(compilation_time),
+    }
+}
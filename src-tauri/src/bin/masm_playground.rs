@@ -0,0 +1,521 @@
+use miden_app_dj_lib::debug_log::DebugEntry;
+use miden_app_dj_lib::diagnostics::{collect_diagnostics, Diagnostic};
+use miden_app_dj_lib::inputs::parse_inputs_json;
+use miden_app_dj_lib::playground::{
+    ErrorKind, ExecutionRequest, ModuleSource, VerifyRequest, PLAYGROUND,
+};
+use miden_app_dj_lib::proving::{ProofHashFunction, ProvingOptionsRequest};
+use miden_app_dj_lib::test_runner::{TestReportFormat, TestSuite};
+use miden_vm::assembly::DefaultSourceManager;
+use serde::Serialize;
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+const USAGE: &str = "Usage: masm-playground <command> [options]
+
+Commands:
+  run <program.masm>      Execute a program and print its output stack
+  prove <program.masm>    Execute a program and write a proof of the execution
+  verify [program.masm]   Verify a proof written by `prove`
+  hash <program.masm>     Print the MAST root of a program without running it
+  test <suite.json>       Run the cases of a test suite
+
+Options:
+  --inputs <file>             Program inputs in the Miden `.inputs` JSON format
+  --module <path>=<file>      Library module the program can import, e.g. myproj::math=math.masm
+  --proof <file>              Proof file (default: the program path with a .proof extension)
+  --outputs <file>            Stack outputs file (default: the proof path with a .outputs extension)
+  --program-hash <hash>       Program hash to verify against, instead of assembling the program
+  --security-level <bits>     Proof security level: 96 (default) or 128
+  --hash-function <name>      Proof hash function: blake3_192, blake3_256, rpo or rpx
+  --format <format>           Test report format: tap (default), junit or json
+  --json                      Print the same JSON as the web API instead of plain text
+
+Exit codes:
+  0  success                  5  execution error
+  1  proof rejected or        6  proving error
+     test failed
+  2  usage or file error      7  cycle limit exceeded
+  3  assembly error           8  timeout
+  4  invalid inputs";
+
+/// The proof did not verify, or a test case failed.
+const EXIT_REJECTED: u8 = 1;
+/// Bad arguments, or a file that could not be read or written.
+const EXIT_USAGE: u8 = 2;
+
+fn exit_code(kind: ErrorKind) -> u8 {
+    match kind {
+        ErrorKind::Assembly => 3,
+        ErrorKind::Inputs => 4,
+        ErrorKind::Execution => 5,
+        ErrorKind::Proving => 6,
+        ErrorKind::CycleLimitExceeded => 7,
+        // The CLI runs without a progress handle, so it never sees a cancelled run
+        ErrorKind::Timeout | ErrorKind::Cancelled => 8,
+    }
+}
+
+#[derive(Default)]
+struct Args {
+    command: String,
+    program: Option<PathBuf>,
+    inputs: Option<PathBuf>,
+    modules: Vec<(String, PathBuf)>,
+    proof: Option<PathBuf>,
+    outputs: Option<PathBuf>,
+    program_hash: Option<String>,
+    security_level: Option<u32>,
+    hash_function: Option<ProofHashFunction>,
+    format: Option<TestReportFormat>,
+    json: bool,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args {
+            command: args.next().ok_or("Missing command")?,
+            ..Default::default()
+        };
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("Missing value for {arg}"));
+            match arg.as_str() {
+                "--inputs" => parsed.inputs = Some(value()?.into()),
+                "--module" => {
+                    let module = value()?;
+                    let (path, file) = module.split_once('=').ok_or(format!(
+                        "Expected <path>=<file> for --module, got '{module}'"
+                    ))?;
+                    parsed.modules.push((path.to_string(), file.into()));
+                }
+                "--proof" => parsed.proof = Some(value()?.into()),
+                "--outputs" => parsed.outputs = Some(value()?.into()),
+                "--program-hash" => parsed.program_hash = Some(value()?),
+                "--security-level" => {
+                    let level = value()?;
+                    let level = level
+                        .parse()
+                        .map_err(|e| format!("Invalid security level '{level}': {e}"))?;
+                    parsed.security_level = Some(level);
+                }
+                "--hash-function" => {
+                    let name = value()?;
+                    let hash_function = serde_json::from_value(Value::String(name.clone()))
+                        .map_err(|_| format!("Unknown hash function '{name}'"))?;
+                    parsed.hash_function = Some(hash_function);
+                }
+                "--format" => {
+                    let name = value()?;
+                    let format = serde_json::from_value(Value::String(name.clone()))
+                        .map_err(|_| format!("Unknown report format '{name}'"))?;
+                    parsed.format = Some(format);
+                }
+                "--json" => parsed.json = true,
+                _ if arg.starts_with("--") => return Err(format!("Unknown option {arg}")),
+                _ if parsed.program.is_none() => parsed.program = Some(arg.into()),
+                _ => return Err(format!("Unexpected argument '{arg}'")),
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn program_path(&self) -> Result<&Path, String> {
+        self.program
+            .as_deref()
+            .ok_or_else(|| format!("Missing program file for `{}`", self.command))
+    }
+
+    fn proof_path(&self) -> Result<PathBuf, String> {
+        match &self.proof {
+            Some(path) => Ok(path.clone()),
+            None => Ok(self.program_path()?.with_extension("proof")),
+        }
+    }
+
+    fn outputs_path(&self) -> Result<PathBuf, String> {
+        match &self.outputs {
+            Some(path) => Ok(path.clone()),
+            None => Ok(self.proof_path()?.with_extension("outputs")),
+        }
+    }
+
+    fn read_inputs(&self) -> Result<Option<Value>, CliError> {
+        let Some(path) = &self.inputs else {
+            return Ok(None);
+        };
+        let inputs = read_file(path)?;
+        parse_inputs_json(&inputs)
+            .map(Some)
+            .map_err(|e| CliError::new(exit_code(ErrorKind::Inputs), e))
+    }
+
+    fn request(&self) -> Result<ExecutionRequest, CliError> {
+        let mut modules = Vec::with_capacity(self.modules.len());
+        for (path, file) in &self.modules {
+            modules.push(ModuleSource {
+                path: path.clone(),
+                source: read_file(file)?,
+            });
+        }
+        Ok(ExecutionRequest {
+            program: read_file(self.program_path()?)?,
+            inputs: self.read_inputs()?,
+            modules,
+            proving_options: ProvingOptionsRequest {
+                security_level: self.security_level,
+                hash_function: self.hash_function,
+            },
+            ..Default::default()
+        })
+    }
+}
+
+/// Why a command failed, and the exit code reporting it.
+struct CliError {
+    code: u8,
+    message: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl CliError {
+    fn new(code: u8, message: String) -> Self {
+        CliError {
+            code,
+            message,
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::new(EXIT_USAGE, message)
+    }
+}
+
+#[derive(Serialize)]
+struct HashResult {
+    success: bool,
+    program_hash: Option<String>,
+    error: Option<String>,
+    error_kind: Option<ErrorKind>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+fn read_file(path: &Path) -> Result<String, CliError> {
+    fs::read_to_string(path).map_err(|e| {
+        CliError::new(
+            EXIT_USAGE,
+            format!("Failed to read {}: {e}", path.display()),
+        )
+    })
+}
+
+fn write_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), CliError> {
+    fs::write(path, contents).map_err(|e| {
+        CliError::new(
+            EXIT_USAGE,
+            format!("Failed to write {}: {e}", path.display()),
+        )
+    })
+}
+
+fn print_json(value: &impl Serialize) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
+
+/// Prints decorator output to stderr, so it does not mix with the results on stdout.
+fn print_debug_log(debug_log: &[DebugEntry], truncated: bool) {
+    for entry in debug_log {
+        let location = entry
+            .location
+            .as_ref()
+            .map(|loc| format!(" at {}:{}:{}", loc.file, loc.line, loc.column))
+            .unwrap_or_default();
+        let values: Vec<String> = entry
+            .values
+            .iter()
+            .map(|value| {
+                let text = value.value.as_deref().unwrap_or("-");
+                match entry.kind.as_str() {
+                    "mem" | "local" => format!("{}={text}", value.index),
+                    _ => text.to_string(),
+                }
+            })
+            .collect();
+        let more = if entry.truncated { ", ..." } else { "" };
+        eprintln!(
+            "[cycle {}] {}{location}: [{}{more}]",
+            entry.cycle,
+            entry.decorator,
+            values.join(", ")
+        );
+    }
+    if truncated {
+        eprintln!("[debug log truncated]");
+    }
+}
+
+/// Turns a failed result into a [CliError], keeping its diagnostics for the plain text output.
+fn failure(
+    error: Option<String>,
+    error_kind: Option<ErrorKind>,
+    diagnostics: Vec<Diagnostic>,
+) -> CliError {
+    CliError {
+        code: exit_code(error_kind.unwrap_or(ErrorKind::Execution)),
+        message: error.unwrap_or_default(),
+        diagnostics,
+    }
+}
+
+fn run(args: &Args) -> Result<(), CliError> {
+    let result = PLAYGROUND.execute(&args.request()?);
+    if args.json {
+        print_json(&result);
+    } else {
+        print_debug_log(&result.debug_log, result.debug_log_truncated);
+    }
+    if !result.success {
+        return Err(failure(result.error, result.error_kind, result.diagnostics));
+    }
+    if !args.json {
+        println!("Program hash: {}", result.program_hash.unwrap_or_default());
+        println!("Cycles: {}", result.cycles.unwrap_or_default());
+        if let Some(summary) = &result.trace_summary {
+            println!(
+                "Trace rows: main {}, chiplets {}, range checker {} (padded to {})",
+                summary.main_trace_len,
+                summary.chiplets.total,
+                summary.range_checker_len,
+                summary.padded_trace_len
+            );
+        }
+        println!(
+            "Stack outputs: [{}]",
+            result.stack_outputs.unwrap_or_default().join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn prove(args: &Args) -> Result<(), CliError> {
+    let proof_path = args.proof_path()?;
+    let outputs_path = args.outputs_path()?;
+
+    let result = PLAYGROUND.prove(&args.request()?);
+    if args.json {
+        print_json(&result);
+    }
+    let (Some(proof_bytes), Some(stack_outputs)) = (&result.proof_bytes, &result.stack_outputs)
+    else {
+        return Err(failure(result.error, result.error_kind, result.diagnostics));
+    };
+
+    write_file(&proof_path, proof_bytes)?;
+    write_file(
+        &outputs_path,
+        serde_json::to_string_pretty(stack_outputs).unwrap(),
+    )?;
+
+    if !args.json {
+        println!("Program hash: {}", result.program_hash.unwrap_or_default());
+        println!("Stack outputs: [{}]", stack_outputs.join(", "));
+        if let Some(options) = &result.proving_options {
+            println!(
+                "Security: {} bits ({} bits conjectured)",
+                options.security_level, options.conjectured_security
+            );
+        }
+        println!(
+            "Proof: {} ({} bytes)",
+            proof_path.display(),
+            proof_bytes.len()
+        );
+        println!("Outputs: {}", outputs_path.display());
+    }
+    Ok(())
+}
+
+fn verify(args: &Args) -> Result<(), CliError> {
+    let program_hash = match &args.program_hash {
+        Some(hash) => hash.clone(),
+        None => program_hash(args)?,
+    };
+
+    let proof_path = args
+        .proof_path()
+        .map_err(|_| "Either a program file or --proof is needed to find the proof".to_string())?;
+    let proof_bytes = fs::read(&proof_path).map_err(|e| {
+        CliError::new(
+            EXIT_USAGE,
+            format!("Failed to read {}: {e}", proof_path.display()),
+        )
+    })?;
+    let outputs_path = args.outputs_path()?;
+    let stack_outputs = serde_json::from_str(&read_file(&outputs_path)?).map_err(|e| {
+        CliError::new(
+            EXIT_USAGE,
+            format!("Invalid stack outputs in {}: {e}", outputs_path.display()),
+        )
+    })?;
+
+    let result = PLAYGROUND.verify(&VerifyRequest {
+        program_hash,
+        inputs: args.read_inputs()?,
+        stack_outputs,
+        proof_bytes,
+    });
+    if args.json {
+        print_json(&result);
+    }
+    if !result.success {
+        return Err(CliError::new(
+            EXIT_REJECTED,
+            result.error.unwrap_or_default(),
+        ));
+    }
+    if !args.json {
+        println!(
+            "Proof verified at {} bits of security",
+            result.security_level.unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+fn hash(args: &Args) -> Result<(), CliError> {
+    let result = match program_hash(args) {
+        Ok(hash) => HashResult {
+            success: true,
+            program_hash: Some(hash),
+            error: None,
+            error_kind: None,
+            diagnostics: Vec::new(),
+        },
+        Err(e) if e.code == exit_code(ErrorKind::Assembly) => HashResult {
+            success: false,
+            program_hash: None,
+            error: Some(e.message),
+            error_kind: Some(ErrorKind::Assembly),
+            diagnostics: e.diagnostics,
+        },
+        Err(e) => return Err(e),
+    };
+    if args.json {
+        print_json(&result);
+    }
+    match result.program_hash {
+        Some(hash) if !args.json => println!("{hash}"),
+        Some(_) => {}
+        None => return Err(failure(result.error, result.error_kind, result.diagnostics)),
+    }
+    Ok(())
+}
+
+fn test(args: &Args) -> Result<(), CliError> {
+    let path = args.program_path()?;
+    let mut suite: TestSuite = serde_json::from_str(&read_file(path)?).map_err(|e| {
+        CliError::new(
+            EXIT_USAGE,
+            format!("Invalid test suite {}: {e}", path.display()),
+        )
+    })?;
+    for (path, file) in &args.modules {
+        suite.modules.push(ModuleSource {
+            path: path.clone(),
+            source: read_file(file)?,
+        });
+    }
+
+    let report = PLAYGROUND.test(&suite);
+    let format = match args.format {
+        Some(format) => format,
+        None if args.json => TestReportFormat::Json,
+        None => TestReportFormat::Tap,
+    };
+    print!("{}", report.render(format));
+    if format == TestReportFormat::Json {
+        println!();
+    }
+    if !report.success {
+        return Err(CliError::new(
+            EXIT_REJECTED,
+            format!(
+                "{} of {} test cases did not pass",
+                report.failed + report.errors,
+                report.total
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Assembles the program without running it and returns its MAST root.
+fn program_hash(args: &Args) -> Result<String, CliError> {
+    let request = args.request()?;
+    let source_manager = Arc::new(DefaultSourceManager::default());
+    let assembly_error = |message, diagnostics| CliError {
+        code: exit_code(ErrorKind::Assembly),
+        message,
+        diagnostics,
+    };
+    let assembler = PLAYGROUND
+        .assembler(&source_manager, &request.modules)
+        .map_err(|e| assembly_error(e.message, e.diagnostics))?;
+    let program = assembler
+        .assemble_program(request.program.as_str())
+        .map_err(|e| {
+            assembly_error(
+                format!("Assembly error: {e}"),
+                collect_diagnostics(e.as_ref()),
+            )
+        })?;
+    Ok(program.hash().to_string())
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(args) if matches!(args.command.as_str(), "-h" | "--help" | "help") => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {e}\nRun `masm-playground --help` for usage.");
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    let outcome = match args.command.as_str() {
+        "run" => run(&args),
+        "prove" => prove(&args),
+        "verify" => verify(&args),
+        "hash" => hash(&args),
+        "test" => test(&args),
+        command => {
+            eprintln!("error: Unknown command '{command}'\n\n{USAGE}");
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    match outcome {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            // Errors always go to stderr, so `--json` output on stdout stays parseable
+            eprintln!("error: {}", e.message);
+            for diagnostic in &e.diagnostics {
+                if let (Some(file), Some(line), Some(column)) =
+                    (&diagnostic.file, diagnostic.line, diagnostic.column)
+                {
+                    eprintln!("  --> {file}:{line}:{column}");
+                }
+            }
+            ExitCode::from(e.code)
+        }
+    }
+}
@@ -1,43 +1,21 @@
-use miden_processor::ExecutionOptions;
-use miden_stdlib::StdLibrary;
-use miden_vm::{
-    assembly::DefaultSourceManager, execute, prove, AdviceInputs, Assembler, DefaultHost,
-    ProvingOptions, StackInputs,
+use crate::artifacts::ExportRequest;
+use crate::compile::CompileRequest;
+use crate::disassembler::DisassembleRequest;
+use crate::inputs::parse_inputs_json;
+use crate::jobs::{JobKind, JobRequest, JOBS};
+use crate::mast_tree::MastTreeRequest;
+use crate::playground::{
+    ErrorKind, ExecutionRequest, ExecutionResult, ExecutionSettings, ModuleSource, ProofResult,
+    VerificationResult, VerifyRequest, PLAYGROUND,
 };
-use serde::{Deserialize, Serialize};
+use crate::proving::ProvingOptionsRequest;
+use crate::test_runner::{TestReportFormat, TestSuite};
+use crate::trace_export::{TraceExport, TraceFormat, TraceRequest};
 use serde_json::Value;
-use std::sync::Arc;
-use std::time::Instant;
+use tauri::{AppHandle, Emitter};
 
-#[derive(Deserialize)]
-pub struct ExecutionRequest {
-    pub program: String,
-    pub inputs: Option<Value>,
-}
-
-#[derive(Serialize)]
-pub struct ExecutionResult {
-    pub success: bool,
-    pub stack_outputs: Option<Vec<String>>,
-    pub program_hash: Option<String>,
-    pub cycles: Option<u32>,
-    pub error: Option<String>,
-    pub compilation_time_ms: Option<f64>,
-    pub execution_time_ms: Option<f64>,
-    pub total_time_ms: Option<f64>,
-}
-
-#[derive(Serialize)]
-pub struct ProofResult {
-    pub success: bool,
-    pub proof_bytes: Option<Vec<u8>>,
-    pub program_hash: Option<String>,
-    pub stack_outputs: Option<Vec<String>>,
-    pub error: Option<String>,
-    pub compilation_time_ms: Option<f64>,
-    pub proving_time_ms: Option<f64>,
-    pub total_time_ms: Option<f64>,
-}
+/// Tauri event carrying the `JobMessage`s of jobs started with [start_job].
+const JOB_PROGRESS_EVENT: &str = "job-progress";
 
 #[tauri::command]
 pub fn greet(name: &str) -> String {
@@ -46,52 +24,42 @@ pub fn greet(name: &str) -> String {
 
 #[tauri::command]
 pub fn instantiate() -> String {
-    let source_manager = Arc::new(DefaultSourceManager::default());
-
-    let assembler = Assembler::default()
-        .with_debug_mode(true)
-        .with_static_library(StdLibrary::default())
-        .unwrap();
-
-    let program = assembler
-        .assemble_program("begin push.8 push.5 add swap drop end")
-        .unwrap();
-
-    let stack_inputs = StackInputs::default();
-    let advice_inputs = AdviceInputs::default();
-    let mut host = DefaultHost::default();
-    let exec_options = ExecutionOptions::default();
-
-    let trace = execute(
-        &program,
-        stack_inputs.clone(),
-        advice_inputs.clone(),
-        &mut host,
-        exec_options,
-        source_manager.clone(),
-    )
-    .unwrap();
+    let request = ExecutionRequest {
+        program: "begin push.8 push.5 add swap drop end".to_string(),
+        ..Default::default()
+    };
+    let result = PLAYGROUND.execute(&request);
 
-    let stack_top: Vec<_> = trace
-        .stack_outputs()
-        .stack_truncated(1)
-        .iter()
-        .map(|f| f.to_string())
-        .collect();
-    stack_top[0].to_string()
+    result
+        .stack_outputs
+        .and_then(|stack| stack.into_iter().next())
+        .unwrap_or_else(|| result.error.unwrap_or_default())
 }
 
 #[tauri::command]
 pub fn exec_program(program: &str) -> Result<String, String> {
-    exec_program_with_inputs(program, None)
+    exec_program_with_inputs(program, None, None, None, None)
 }
 
 #[tauri::command]
 pub fn exec_program_with_inputs(
     program: &str,
     inputs_json: Option<String>,
+    settings: Option<ExecutionSettings>,
+    modules: Option<Vec<ModuleSource>>,
+    mast: Option<Vec<u8>>,
 ) -> Result<String, String> {
-    let result = execute_program_impl(program, inputs_json.as_deref());
+    let result = match parse_inputs(inputs_json) {
+        Ok(inputs) => PLAYGROUND.execute(&ExecutionRequest {
+            program: program.to_string(),
+            mast,
+            inputs,
+            modules: modules.unwrap_or_default(),
+            settings: settings.unwrap_or_default(),
+            proving_options: ProvingOptionsRequest::default(),
+        }),
+        Err(e) => ExecutionResult::failure(ErrorKind::Inputs, e),
+    };
     Ok(serde_json::to_string(&result).unwrap())
 }
 
@@ -99,462 +67,199 @@ pub fn exec_program_with_inputs(
 pub fn generate_proof_with_inputs(
     program: &str,
     inputs_json: Option<String>,
+    modules: Option<Vec<ModuleSource>>,
+    settings: Option<ExecutionSettings>,
+    proving_options: Option<ProvingOptionsRequest>,
+    mast: Option<Vec<u8>>,
 ) -> Result<String, String> {
-    let result = generate_proof_impl(program, inputs_json.as_deref());
+    let result = match parse_inputs(inputs_json) {
+        Ok(inputs) => PLAYGROUND.prove(&ExecutionRequest {
+            program: program.to_string(),
+            mast,
+            inputs,
+            modules: modules.unwrap_or_default(),
+            settings: settings.unwrap_or_default(),
+            proving_options: proving_options.unwrap_or_default(),
+        }),
+        Err(e) => ProofResult::failure(ErrorKind::Inputs, e),
+    };
     Ok(serde_json::to_string(&result).unwrap())
 }
 
 #[tauri::command]
-pub fn get_example_programs() -> String {
-    let prime_generator = r#"use.std::sys
-
-# append the current number to the prime list
-proc.append
-    # initial state
-    # [prime, i, n, primes..]
-
-    # [prime, prime, i, n, primes..]
-    dup
-
-    # [i, prime, prime, i, n, primes..]
-    dup.2
-
-    # [prime, i, n, primes..]
-    mem_store
-
-    # [i++, n, primes..]
-    swap.2
-    swap
-    add.1
-end
-
-# push a boolean on whether or not the program should continue
-proc.should_continue
-    # initial state
-    # [i, n, primes..]
-
-    # [i, n, i, n, primes..]
-    dup.1
-    dup.1
-
-    # [should_continue, i, n, primes..]
-    neq
-end
-
-# define if check should continue
-# will return two flags: one if the loop should continue, the other if candidate is prime
-proc.is_not_prime_should_continue
-    # initial state
-    # [j, candidate, i, n, primes..]
-
-    # load the current prime
-    # [prime, j, candidate, i, n, primes..]
-    dup
-    mem_load
-
-    # push return flags
-    # [continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-    push.0.1
-
-    # a composite number have its smallest prime squared lesser than itself.
-    # if the squared prime is bigger than the candidate, and provided we iterate
-    # a list of ordered primes, then the number is a prime.
-    #
-    # this will also protect the algorithm from overflowing the list of current list of primes
-    # because the squared prime will always halt the iteration before the end of the list is
-    # reached
-    #
-    # [squared prime, continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-    dup.2
-    dup
-    mul
-    # [candidate, squared prime, continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-    dup.5
-    # [continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-    gt
-    if.true
-        drop
-        drop
-        push.1.0
-    end
-
-    # check mod only if should continue loop
-    dup
-    if.true
-        # [remainder, continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-        dup.4
-        dup.3
-        u32assert2 u32mod
-
-        # if remainder is zero, then the number is divisible by prime; hence isn't prime
-        # [continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-        eq.0
-        if.true
-            drop
-            drop
-            push.0.0
-        end
-    end
-
-    # [continue loop?, is prime?, j, candidate, i, n, primes..]
-    swap.2
-    drop
-    swap
-end
-
-# check if current candidate isn't a prime
-proc.is_not_prime
-    # initial state
-    # [candidate, i, n, primes..]
-
-    # create a counter `j` to iterate over primes
-    # [j, candidate, i, n, primes..]
-    push.0
-
-    exec.is_not_prime_should_continue
-    while.true
-        # [j, candidate, i, n, primes..]
-        drop
-        add.1
-
-        # [is prime?, j, candidate, i, n, primes..]
-        exec.is_not_prime_should_continue
-    end
-
-    # [is not prime?, candidate, i, n, primes..]
-    swap
-    drop
-    eq.0
-end
-
-# calculate and push next prime to the stack
-proc.next
-    # initial state
-    # [i, n, primes..]
-
-    # create a candidate
-    # [candidate, i, n, primes..]
-    dup.2
-    add.2
-
-    exec.is_not_prime
-    while.true
-        # [candidate, i, n, primes..]
-        add.2
-        exec.is_not_prime
-    end
-
-    # [i, n, primes..]
-    exec.append
-end
-
-# the stack is expected to contain on its top the desired primes count. this can be achieved via the
-# *.inputs file.
-#
-# the end of the program will return a stack containing all the primes, up to the nth argument.
-#
-# example:
-#
-# input:
-# [50, ..]
-#
-# output:
-# [229, 227, 223, 211, 199, 197, 193, 191, 181, 179, 173, 167, 163, 157, 151, 149]
-begin
-    # create a counter `i`
-    push.0
-
-    # 2 and 3 are the unique sequential primes. by pushing these manually, we can iterate
-    # the candidates in chunks of 2
-
-    # append first known prime
-    push.2
-    exec.append
-
-    # append second known prime
-    push.3
-    exec.append
-
-    # find next primes until limit is reached
-    exec.should_continue
-    while.true
-        exec.next
-        exec.should_continue
-    end
-
-    # drop the counters
-    drop
-    drop
-
-    # Truncate stack to make constraints happy
-    exec.sys::truncate_stack
-end"#;
-
-    let examples = vec![
-        ("Basic Addition", "# Simple addition example\n# Pushes 3 and 5, then adds them\n# Result: 8 on stack top, 3 below it\nbegin\n    push.3\n    push.5\n    add\n    swap\n    drop\nend"),
-        ("Input Stack Demo", "# Demonstrates using input stack values\n# Takes two numbers from input and adds them\n# Try with: [\"10\", \"20\"] → Result: 30\nbegin\n    # Input values are already on stack\n    # Stack: [20, 10] (top to bottom)\n    add\n    # Stack: [30]\nend"),
-        ("Fibonacci Numbers", "# Generates first 8 Fibonacci numbers\n# Uses iterative approach with stack manipulation\n# Result: F(8)=21 on top, previous numbers below\nbegin\n    push.1 push.1  # Start with F(0)=1, F(1)=1\n    push.6         # Generate 6 more numbers\n    repeat.6\n        dup.1      # Duplicate second element\n        add        # Add top two elements\n        swap       # Swap for next iteration\n    end\n    drop           # Remove counter\nend"),
-        ("Prime Generator", prime_generator),
-        ("Conditional Logic", "# Compares two numbers and keeps the larger one\n# Demonstrates if-else branching\n# Compares 15 and 10, keeps 15\nbegin\n    push.15 push.10  # Stack: [10, 15]\n    dup.1 gt         # Check if 15 > 10\n    if.true\n        swap         # Put larger number on top\n    end\n    drop             # Remove smaller number\nend"),
-        ("Memory Operations", "# Shows memory store and load operations\n# Stores values in memory addresses 0 and 1\n# Then loads and adds them\nbegin\n    # Store 42 at address 0\n    push.42 push.0 mem_store\n    # Store 100 at address 1  \n    push.100 push.1 mem_store\n    # Load both values and add\n    push.0 mem_load    # Load from address 0\n    push.1 mem_load    # Load from address 1\n    add                # 42 + 100 = 142\nend"),
-        ("Stack Manipulation", "# Demonstrates various stack operations\n# Shows dup, swap, drop operations\n# Final result: [3, 3] (duplicate 3s)\nbegin\n    push.1 push.2 push.3 push.4  # Stack: [4,3,2,1]\n    swap.2                       # Swap top with 3rd: [2,3,4,1]\n    drop                         # Remove top: [3,4,1]\n    dup                          # Duplicate top: [3,3,4,1]\n    swap.2                       # Final: [3,3,1,4]\n    drop drop                    # Keep only: [3,3]\nend"),
-        ("Counter with Input", "# Takes input number and adds 5 to it\n# Simple arithmetic with input\n# Try with: [\"7\"] → Result: 12\nbegin\n    # Input value is on stack\n    push.5       # Add 5 to it\n    add          # Perform addition\nend"),
-    ];
-
-    serde_json::to_string(&examples).unwrap()
-}
-
-fn parse_stack_inputs(inputs_json: &str) -> Result<StackInputs, String> {
-    let parsed: Value =
-        serde_json::from_str(inputs_json).map_err(|e| format!("Invalid JSON: {e}"))?;
-
-    let mut inputs = Vec::new();
-
-    if let Some(operand_stack) = parsed.get("operand_stack") {
-        if let Some(stack_array) = operand_stack.as_array() {
-            for item in stack_array {
-                if let Some(val_str) = item.as_str() {
-                    let val: u64 = val_str
-                        .parse()
-                        .map_err(|e| format!("Invalid number '{val_str}': {e}"))?;
-                    inputs.push(val);
-                } else if let Some(val_num) = item.as_u64() {
-                    inputs.push(val_num);
-                }
-            }
-        }
-    }
-
-    // Reverse because stack inputs are pushed in reverse order
-    inputs.reverse();
-
-    StackInputs::try_from_ints(inputs).map_err(|e| format!("Failed to create stack inputs: {e}"))
-}
-
-pub fn execute_program_impl(program: &str, inputs_json: Option<&str>) -> ExecutionResult {
-    let total_start = Instant::now();
-    let source_manager = Arc::new(DefaultSourceManager::default());
-
-    // Create assembler with source manager and standard library
-    let compilation_start = Instant::now();
-    let assembler = match Assembler::default()
-        .with_debug_mode(true)
-        .with_static_library(StdLibrary::default())
-    {
-        Ok(asm) => asm,
-        Err(e) => {
-            return ExecutionResult {
-                success: false,
-                stack_outputs: None,
-                program_hash: None,
-                cycles: None,
-                error: Some(format!("Failed to configure assembler: {e}")),
-                compilation_time_ms: None,
-                execution_time_ms: None,
-                total_time_ms: Some(total_start.elapsed().as_millis() as f64),
-            }
-        }
-    };
-
-    // Parse and set up stack inputs
-    let stack_inputs = if let Some(inputs_str) = inputs_json {
-        match parse_stack_inputs(inputs_str) {
-            Ok(inputs) => inputs,
-            Err(e) => {
-                return ExecutionResult {
-                    success: false,
-                    stack_outputs: None,
-                    program_hash: None,
-                    cycles: None,
-                    error: Some(e),
-                    compilation_time_ms: None,
-                    execution_time_ms: None,
-                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
-                }
-            }
-        }
-    } else {
-        StackInputs::default()
-    };
-
-    // Assemble the program
-    let program = match assembler.assemble_program(program) {
-        Ok(prog) => prog,
-        Err(e) => {
-            return ExecutionResult {
-                success: false,
-                stack_outputs: None,
-                program_hash: None,
-                cycles: None,
-                error: Some(format!("Assembly error: {e}")),
-                compilation_time_ms: Some(compilation_start.elapsed().as_millis() as f64),
-                execution_time_ms: None,
-                total_time_ms: Some(total_start.elapsed().as_millis() as f64),
-            }
-        }
+pub fn verify_proof(
+    program_hash: &str,
+    inputs_json: Option<String>,
+    stack_outputs: Vec<String>,
+    proof_bytes: Vec<u8>,
+) -> Result<String, String> {
+    let result = match parse_inputs(inputs_json) {
+        Ok(inputs) => PLAYGROUND.verify(&VerifyRequest {
+            program_hash: program_hash.to_string(),
+            inputs,
+            stack_outputs,
+            proof_bytes,
+        }),
+        Err(e) => VerificationResult::failure(e),
     };
+    Ok(serde_json::to_string(&result).unwrap())
+}
 
-    let compilation_time = compilation_start.elapsed().as_millis() as f64;
-
-    let advice_inputs = AdviceInputs::default();
-    let mut host = DefaultHost::default();
-    let exec_options = ExecutionOptions::default();
-
-    // Execute the program
-    let execution_start = Instant::now();
-    let trace = match execute(
-        &program,
-        stack_inputs.clone(),
-        advice_inputs.clone(),
-        &mut host,
-        exec_options,
-        source_manager.clone(),
-    ) {
-        Ok(trace) => trace,
+/// Queues an execution or proving job and emits its progress as `job-progress` events until it
+/// finishes. Returns the queued job, or the reason it was rejected.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn start_job(
+    app: AppHandle,
+    kind: JobKind,
+    program: &str,
+    inputs_json: Option<String>,
+    modules: Option<Vec<ModuleSource>>,
+    settings: Option<ExecutionSettings>,
+    proving_options: Option<ProvingOptionsRequest>,
+    mast: Option<Vec<u8>>,
+) -> Result<String, String> {
+    let inputs = match parse_inputs(inputs_json) {
+        Ok(inputs) => inputs,
         Err(e) => {
-            return ExecutionResult {
-                success: false,
-                stack_outputs: None,
-                program_hash: None,
-                cycles: None,
-                error: Some(format!("Execution error: {e}")),
-                compilation_time_ms: Some(compilation_time),
-                execution_time_ms: None,
-                total_time_ms: Some(total_start.elapsed().as_millis() as f64),
-            }
+            let mut rejected = JOBS.queue_status();
+            rejected.success = false;
+            rejected.error = Some(e);
+            return Ok(serde_json::to_string(&rejected).unwrap());
         }
     };
-
-    let execution_time = execution_start.elapsed().as_millis() as f64;
-    let total_time = total_start.elapsed().as_millis() as f64;
-
-    // Get stack outputs (show more elements)
-    let stack_outputs: Vec<String> = trace
-        .stack_outputs()
-        .stack_truncated(16)
-        .iter()
-        .map(|f| f.to_string())
-        .collect();
-
-    ExecutionResult {
-        success: true,
-        stack_outputs: Some(stack_outputs),
-        program_hash: Some(program.hash().to_string()),
-        cycles: Some(trace.get_trace_len() as u32),
-        error: None,
-        compilation_time_ms: Some(compilation_time),
-        execution_time_ms: Some(execution_time),
-        total_time_ms: Some(total_time),
+    let submitted = JOBS.submit(JobRequest {
+        kind,
+        request: ExecutionRequest {
+            program: program.to_string(),
+            mast,
+            inputs,
+            modules: modules.unwrap_or_default(),
+            settings: settings.unwrap_or_default(),
+            proving_options: proving_options.unwrap_or_default(),
+        },
+    });
+
+    if let Some(job) = &submitted.job {
+        let id = job.id;
+        std::thread::spawn(move || {
+            JOBS.follow(id, |message| app.emit(JOB_PROGRESS_EVENT, message).is_ok())
+        });
     }
+    Ok(serde_json::to_string(&submitted).unwrap())
 }
 
-pub fn generate_proof_impl(program: &str, inputs_json: Option<&str>) -> ProofResult {
-    let total_start = Instant::now();
-    let source_manager = Arc::new(DefaultSourceManager::default());
+#[tauri::command]
+pub fn cancel_job(id: u64) -> String {
+    serde_json::to_string(&JOBS.cancel(id)).unwrap()
+}
 
-    let compilation_start = Instant::now();
-    let assembler = match Assembler::default()
-        .with_debug_mode(true)
-        .with_static_library(StdLibrary::default())
-    {
-        Ok(asm) => asm,
-        Err(e) => {
-            return ProofResult {
-                success: false,
-                proof_bytes: None,
-                program_hash: None,
-                stack_outputs: None,
-                error: Some(format!("Failed to configure assembler: {e}")),
-                compilation_time_ms: None,
-                proving_time_ms: None,
-                total_time_ms: Some(total_start.elapsed().as_millis() as f64),
-            }
-        }
-    };
+/// Assembles a program and returns its serialized MAST and `.masp` package.
+#[tauri::command]
+pub fn export_program(
+    program: &str,
+    modules: Option<Vec<ModuleSource>>,
+    name: Option<String>,
+) -> String {
+    let result = PLAYGROUND.export(&ExportRequest {
+        program: program.to_string(),
+        modules: modules.unwrap_or_default(),
+        name,
+    });
+    serde_json::to_string(&result).unwrap()
+}
 
-    // Parse stack inputs
-    let stack_inputs = if let Some(inputs_str) = inputs_json {
-        match parse_stack_inputs(inputs_str) {
-            Ok(inputs) => inputs,
-            Err(e) => {
-                return ProofResult {
-                    success: false,
-                    proof_bytes: None,
-                    program_hash: None,
-                    stack_outputs: None,
-                    error: Some(e),
-                    compilation_time_ms: None,
-                    proving_time_ms: None,
-                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
-                }
-            }
-        }
-    } else {
-        StackInputs::default()
-    };
+/// Assembles a program without running it and returns its MAST root, procedures and warnings.
+#[tauri::command]
+pub fn compile_program(program: Option<String>, modules: Option<Vec<ModuleSource>>) -> String {
+    let result = PLAYGROUND.compile(&CompileRequest {
+        program: program.unwrap_or_default(),
+        modules: modules.unwrap_or_default(),
+    });
+    serde_json::to_string(&result).unwrap()
+}
 
-    // Assemble the program
-    let program = match assembler.assemble_program(program) {
-        Ok(prog) => prog,
-        Err(e) => {
-            return ProofResult {
-                success: false,
-                proof_bytes: None,
-                program_hash: None,
-                stack_outputs: None,
-                error: Some(format!("Assembly error: {e}")),
-                compilation_time_ms: Some(compilation_start.elapsed().as_millis() as f64),
-                proving_time_ms: None,
-                total_time_ms: Some(total_start.elapsed().as_millis() as f64),
-            }
-        }
-    };
+/// Describes the MAST of a program, or of a precompiled one, as a tree for rendering.
+#[tauri::command]
+pub fn get_mast_tree(
+    program: Option<String>,
+    modules: Option<Vec<ModuleSource>>,
+    mast: Option<Vec<u8>>,
+) -> String {
+    let result = PLAYGROUND.mast_tree(&MastTreeRequest {
+        program: program.unwrap_or_default(),
+        mast,
+        modules: modules.unwrap_or_default(),
+    });
+    serde_json::to_string(&result).unwrap()
+}
 
-    let compilation_time = compilation_start.elapsed().as_millis() as f64;
+/// Maps each instruction of a program, or of a precompiled one, to the VM operations it was
+/// lowered to.
+#[tauri::command]
+pub fn disassemble_program(
+    program: Option<String>,
+    modules: Option<Vec<ModuleSource>>,
+    mast: Option<Vec<u8>>,
+) -> String {
+    let result = PLAYGROUND.disassemble(&DisassembleRequest {
+        program: program.unwrap_or_default(),
+        mast,
+        modules: modules.unwrap_or_default(),
+    });
+    serde_json::to_string(&result).unwrap()
+}
 
-    // Generate proof
-    let proving_start = Instant::now();
-    let (outputs, proof) = match prove(
-        &program,
-        stack_inputs,
-        AdviceInputs::default(),
-        &mut DefaultHost::default(),
-        ProvingOptions::default(),
-        source_manager,
-    ) {
-        Ok(result) => result,
-        Err(e) => {
-            return ProofResult {
-                success: false,
-                proof_bytes: None,
-                program_hash: None,
-                stack_outputs: None,
-                error: Some(format!("Proving error: {e}")),
-                compilation_time_ms: Some(compilation_time),
-                proving_time_ms: None,
-                total_time_ms: Some(total_start.elapsed().as_millis() as f64),
-            }
-        }
+/// Executes a program and returns the cycles from `start_cycle` to `end_cycle` of its execution
+/// trace, encoded in `format`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn export_trace(
+    program: &str,
+    inputs_json: Option<String>,
+    modules: Option<Vec<ModuleSource>>,
+    settings: Option<ExecutionSettings>,
+    mast: Option<Vec<u8>>,
+    format: Option<TraceFormat>,
+    start_cycle: Option<u32>,
+    end_cycle: Option<u32>,
+) -> Result<String, String> {
+    let format = format.unwrap_or_default();
+    let result = match parse_inputs(inputs_json) {
+        Ok(inputs) => PLAYGROUND.trace(&TraceRequest {
+            execution: ExecutionRequest {
+                program: program.to_string(),
+                mast,
+                inputs,
+                modules: modules.unwrap_or_default(),
+                settings: settings.unwrap_or_default(),
+                proving_options: ProvingOptionsRequest::default(),
+            },
+            format,
+            start_cycle,
+            end_cycle,
+        }),
+        Err(e) => TraceExport::failure(format, ErrorKind::Inputs, e, Vec::new()),
     };
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+#[tauri::command]
+pub fn get_example_programs() -> String {
+    serde_json::to_string(&PLAYGROUND.examples().list()).unwrap()
+}
 
-    let proving_time = proving_start.elapsed().as_millis() as f64;
-    let total_time = total_start.elapsed().as_millis() as f64;
+#[tauri::command]
+pub fn get_example(id: &str) -> String {
+    serde_json::to_string(&PLAYGROUND.examples().find(id)).unwrap()
+}
 
-    let stack_outputs: Vec<String> = outputs
-        .first()
-        .iter()
-        .take(16)
-        .map(|f| f.to_string())
-        .collect();
+#[tauri::command]
+pub fn run_tests(suite: TestSuite, format: Option<TestReportFormat>) -> String {
+    PLAYGROUND.test(&suite).render(format.unwrap_or_default())
+}
 
-    ProofResult {
-        success: true,
-        proof_bytes: Some(proof.to_bytes()),
-        program_hash: Some(program.hash().to_string()),
-        stack_outputs: Some(stack_outputs),
-        error: None,
-        compilation_time_ms: Some(compilation_time),
-        proving_time_ms: Some(proving_time),
-        total_time_ms: Some(total_time),
-    }
+/// The Tauri front-end sends program inputs as a JSON string rather than an object.
+fn parse_inputs(inputs_json: Option<String>) -> Result<Option<Value>, String> {
+    inputs_json.as_deref().map(parse_inputs_json).transpose()
 }
@@ -0,0 +1,184 @@
+use crate::diagnostics::{collect_diagnostics, Diagnostic};
+use crate::mast_tree::procedure_root_name;
+use crate::playground::{ErrorKind, ModuleSource, Playground};
+use crate::profiler::library_procedures;
+use miden_vm::assembly::{
+    ast::{Module, ModuleKind},
+    DefaultSourceManager, LibraryPath, Parse, ParseOptions,
+};
+use miden_vm::diagnostics::Diagnostic as MietteDiagnostic;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+
+#[derive(Deserialize)]
+pub struct CompileRequest {
+    /// The program to assemble. When it is empty, only `modules` are assembled, as a library.
+    #[serde(default)]
+    pub program: String,
+    #[serde(default)]
+    pub modules: Vec<ModuleSource>,
+}
+
+/// A procedure in the MAST forest of the program.
+#[derive(Serialize)]
+pub struct ProcedureRoot {
+    pub name: String,
+    /// The MAST root of the procedure, which `call` and `exec` refer to it by.
+    pub digest: String,
+    pub entrypoint: bool,
+}
+
+/// A procedure the user's modules export.
+#[derive(Serialize)]
+pub struct LibraryExport {
+    /// Fully qualified name, such as `myproj::math::square`.
+    pub name: String,
+    pub digest: String,
+}
+
+#[derive(Serialize)]
+pub struct CompileResult {
+    pub success: bool,
+    /// The MAST root of the program, which commits to all of its code.
+    pub program_hash: Option<String>,
+    /// Every procedure in the program, including library procedures it uses.
+    pub procedures: Vec<ProcedureRoot>,
+    /// The commitment to the library assembled from the user's modules, if any.
+    pub library_digest: Option<String>,
+    pub exports: Vec<LibraryExport>,
+    /// Problems that did not stop assembly, such as unused imports.
+    pub warnings: Vec<Diagnostic>,
+    pub error: Option<String>,
+    pub error_kind: Option<ErrorKind>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub compilation_time_ms: Option<f64>,
+}
+
+impl CompileResult {
+    fn failure(error: String, diagnostics: Vec<Diagnostic>, compilation_time: f64) -> Self {
+        CompileResult {
+            success: false,
+            program_hash: None,
+            procedures: Vec::new(),
+            library_digest: None,
+            exports: Vec::new(),
+            warnings: Vec::new(),
+            error: Some(error),
+            error_kind: Some(ErrorKind::Assembly),
+            diagnostics,
+            compilation_time_ms: Some(compilation_time),
+        }
+    }
+}
+
+/// Keeps the warnings of a report, dropping the error that wraps them.
+fn warnings_of(report: &dyn MietteDiagnostic) -> Vec<Diagnostic> {
+    collect_diagnostics(report)
+        .into_iter()
+        .filter(|diagnostic| diagnostic.severity == "warning")
+        .collect()
+}
+
+/// The assembler prints warnings rather than returning them, so they are found by parsing the
+/// sources again with warnings treated as errors.
+fn collect_warnings(
+    source_manager: &DefaultSourceManager,
+    program: &str,
+    modules: &[ModuleSource],
+) -> Vec<Diagnostic> {
+    let mut warnings = Vec::new();
+    for module in modules {
+        let Ok(path) = LibraryPath::new(&module.path) else {
+            continue;
+        };
+        let mut parser = Module::parser(ModuleKind::Library);
+        parser.set_warnings_as_errors(true);
+        if let Err(e) = parser.parse_str(path, &module.source, source_manager) {
+            warnings.extend(warnings_of(e.as_ref()));
+        }
+    }
+    if !program.trim().is_empty() {
+        let options = ParseOptions {
+            warnings_as_errors: true,
+            ..ParseOptions::default()
+        };
+        if let Err(e) = program.parse_with_options(source_manager, options) {
+            warnings.extend(warnings_of(e.as_ref()));
+        }
+    }
+    warnings
+}
+
+/// Assembles a program and its modules and describes their procedures, without running anything.
+pub fn compile_program(playground: &Playground, request: &CompileRequest) -> CompileResult {
+    let source_manager = Arc::new(DefaultSourceManager::default());
+    let compilation_start = Instant::now();
+
+    let library = match playground.module_library(&source_manager, &request.modules) {
+        Ok(library) => library,
+        Err(e) => {
+            let compilation_time = compilation_start.elapsed().as_millis() as f64;
+            return CompileResult::failure(e.message, e.diagnostics, compilation_time);
+        }
+    };
+    let program = if request.program.trim().is_empty() && library.is_some() {
+        None
+    } else {
+        match playground.assemble(&source_manager, &request.program, &request.modules) {
+            Ok(program) => Some(program),
+            Err(e) => {
+                let compilation_time = compilation_start.elapsed().as_millis() as f64;
+                return CompileResult::failure(e.message, e.diagnostics, compilation_time);
+            }
+        }
+    };
+    let compilation_time = compilation_start.elapsed().as_millis() as f64;
+
+    // Procedures of the user's modules are named after their exports, like library procedures
+    let names = library_procedures(playground.libraries().iter().chain(library.as_ref()));
+    let procedures = program
+        .as_ref()
+        .map(|program| {
+            let forest = program.mast_forest();
+            let entrypoint = program.entrypoint();
+            forest
+                .procedure_roots()
+                .iter()
+                .map(|&node_id| ProcedureRoot {
+                    name: procedure_root_name(forest, node_id, entrypoint, &names)
+                        .unwrap_or_default(),
+                    digest: forest[node_id].digest().to_string(),
+                    entrypoint: node_id == entrypoint,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let exports = library
+        .as_ref()
+        .map(|library| {
+            library
+                .exports()
+                .map(|name| LibraryExport {
+                    name: name.to_string(),
+                    digest: library.mast_forest()[library.get_export_node_id(name)]
+                        .digest()
+                        .to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    CompileResult {
+        success: true,
+        program_hash: program.map(|program| program.hash().to_string()),
+        procedures,
+        library_digest: library.as_ref().map(|library| library.digest().to_string()),
+        exports,
+        warnings: collect_warnings(&source_manager, &request.program, &request.modules),
+        error: None,
+        error_kind: None,
+        diagnostics: Vec::new(),
+        compilation_time_ms: Some(compilation_time),
+    }
+}
@@ -0,0 +1,218 @@
+use crate::host::PlaygroundHost;
+use crate::limits::run_with_timeout;
+use crate::outputs::{format_felt, OutputFormat};
+use crate::playground::{resolve_source_location, SourceLocation};
+use crate::progress::{interruptible, Progress};
+use miden_core::{DebugOptions, Decorator, Felt};
+use miden_processor::ProcessState;
+use miden_vm::{assembly::DefaultSourceManager, execute_iter, AdviceInputs, Program, StackInputs};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Maximum number of values captured by a single decorator; `debug.mem` over a wide interval
+/// would otherwise read billions of cells.
+const MAX_DEBUG_VALUES: usize = 1024;
+
+/// Maximum number of entries captured per run; a decorator inside a loop runs on every iteration.
+pub(crate) const MAX_DEBUG_ENTRIES: usize = 1024;
+
+/// A value captured by a decorator.
+#[derive(Serialize, Clone, Debug)]
+pub struct DebugValue {
+    /// Stack position, memory address or local index, depending on the decorator.
+    pub index: u32,
+    /// `None` for memory that was never written.
+    pub value: Option<String>,
+}
+
+/// The output of a `debug.*` or `trace.*` decorator.
+#[derive(Serialize, Clone, Debug)]
+pub struct DebugEntry {
+    /// The cycle the decorator ran before.
+    pub cycle: u32,
+    /// `stack`, `mem`, `local`, `adv_stack` or `trace`.
+    pub kind: String,
+    /// The decorator with its arguments resolved, such as `debug.stack.4` or `trace.7`.
+    pub decorator: String,
+    /// Where the instruction the decorator runs before was written. Decorators have no location
+    /// of their own, so one that ends a block points at the last instruction executed instead.
+    pub location: Option<SourceLocation>,
+    /// The execution context the decorator ran in.
+    pub context: u32,
+    pub values: Vec<DebugValue>,
+    /// Whether values were left out past the first 1024.
+    pub truncated: bool,
+}
+
+/// Whether `program` contains any decorator [debug_log] would capture.
+pub fn has_debug_decorators(program: &Program) -> bool {
+    program
+        .mast_forest()
+        .decorators()
+        .iter()
+        .any(|decorator| matches!(decorator, Decorator::Debug(_) | Decorator::Trace(_)))
+}
+
+fn entry(process: &ProcessState, kind: &str, decorator: String) -> DebugEntry {
+    DebugEntry {
+        cycle: process.clk().as_u32(),
+        kind: kind.to_string(),
+        decorator,
+        location: None,
+        context: process.ctx().into(),
+        values: Vec::new(),
+        truncated: false,
+    }
+}
+
+fn push_values(
+    entry: &mut DebugEntry,
+    values: impl Iterator<Item = (u32, Option<Felt>)>,
+    format: OutputFormat,
+) {
+    for (index, value) in values {
+        if entry.values.len() == MAX_DEBUG_VALUES {
+            entry.truncated = true;
+            break;
+        }
+        entry.values.push(DebugValue {
+            index,
+            value: value.map(|value| format_felt(value, format)),
+        });
+    }
+}
+
+/// Captures the values a `debug` decorator asks for.
+pub(crate) fn capture_debug(
+    process: &ProcessState,
+    options: &DebugOptions,
+    format: OutputFormat,
+) -> DebugEntry {
+    let kind = match options {
+        DebugOptions::StackAll | DebugOptions::StackTop(_) => "stack",
+        DebugOptions::MemAll | DebugOptions::MemInterval(..) => "mem",
+        DebugOptions::LocalInterval(..) => "local",
+        DebugOptions::AdvStackTop(_) => "adv_stack",
+    };
+    let mut entry = entry(process, kind, format!("debug.{options}"));
+    let ctx = process.ctx();
+
+    match *options {
+        DebugOptions::StackAll | DebugOptions::StackTop(_) => {
+            let depth = match *options {
+                DebugOptions::StackTop(n) => n as usize,
+                _ => usize::MAX,
+            };
+            let stack = process.get_stack_state();
+            let values = stack.into_iter().take(depth).map(Some);
+            push_values(&mut entry, (0..).zip(values), format);
+        }
+        DebugOptions::MemAll => {
+            let memory = process.get_mem_state(ctx);
+            let values = memory
+                .into_iter()
+                .map(|(address, value)| (address.into(), Some(value)));
+            push_values(&mut entry, values, format);
+        }
+        DebugOptions::MemInterval(start, end) => {
+            let values =
+                (start..=end).map(|address| (address, process.get_mem_value(ctx, address)));
+            push_values(&mut entry, values, format);
+        }
+        DebugOptions::LocalInterval(start, end, num_locals) => {
+            // Locals sit just below the free memory pointer
+            let first_local = (process.fmp() as u32).saturating_sub(num_locals as u32);
+            let values = (start..=end)
+                .take_while(|&index| index < num_locals)
+                .map(|index| {
+                    let address = first_local + index as u32;
+                    (index as u32, process.get_mem_value(ctx, address))
+                });
+            push_values(&mut entry, values, format);
+        }
+        DebugOptions::AdvStackTop(n) => {
+            // The advice stack pops from the back, so its top comes last
+            let stack = process.advice_provider().stack();
+            let depth = if n == 0 { stack.len() } else { n as usize };
+            let values = stack.iter().rev().take(depth).map(|&value| Some(value));
+            push_values(&mut entry, (0..).zip(values), format);
+        }
+    }
+    entry
+}
+
+/// Records a `trace` decorator, which carries nothing but its ID.
+pub(crate) fn capture_trace(process: &ProcessState, trace_id: u32) -> DebugEntry {
+    entry(process, "trace", format!("trace.{trace_id}"))
+}
+
+/// Replays `program` in debug mode and returns the output of its `debug` and `trace` decorators,
+/// reporting each entry to `progress` as well, along with whether entries were left out. At most
+/// [MAX_DEBUG_ENTRIES] are kept.
+///
+/// Regular execution skips these decorators, so this is only worth running for programs that
+/// [have them](has_debug_decorators). A replay still running after `timeout` is stopped, keeping
+/// the entries captured so far without their locations.
+pub fn debug_log(
+    program: &Program,
+    stack_inputs: StackInputs,
+    advice_inputs: AdviceInputs,
+    host: PlaygroundHost,
+    source_manager: Arc<DefaultSourceManager>,
+    progress: &Progress,
+    timeout: Duration,
+) -> (Vec<DebugEntry>, bool) {
+    let mut host = host.with_progress(progress);
+    let states = run_with_timeout(timeout, progress, || {
+        execute_iter(
+            &interruptible(program),
+            stack_inputs,
+            advice_inputs,
+            &mut host,
+            source_manager.clone(),
+        )
+    });
+    let (mut entries, truncated) = host.into_debug_log();
+    let Some(states) = states else {
+        return (entries, true);
+    };
+    if entries.is_empty() {
+        return (entries, truncated);
+    }
+
+    // A decorator runs before the operation of its cycle, which the next state reports
+    let cycles: HashSet<u32> = entries.iter().map(|entry| entry.cycle).collect();
+    let mut next_locations = HashMap::new();
+    let mut last_locations = HashMap::new();
+    let mut last_location = None;
+    for state in states.map_while(Result::ok) {
+        let clk = state.clk.as_u32();
+        let location = state
+            .asmop
+            .as_ref()
+            .and_then(|asmop| asmop.as_ref().location())
+            .cloned();
+        if clk > 0 && cycles.contains(&(clk - 1)) {
+            next_locations.insert(clk - 1, location.clone());
+        }
+        if location.is_some() {
+            last_location = location;
+        }
+        if cycles.contains(&clk) {
+            last_locations.insert(clk, last_location.clone());
+        }
+    }
+    for entry in &mut entries {
+        let location = next_locations
+            .get(&entry.cycle)
+            .cloned()
+            .flatten()
+            .or_else(|| last_locations.get(&entry.cycle).cloned().flatten());
+        entry.location =
+            location.and_then(|location| resolve_source_location(&source_manager, &location));
+        progress.debug(entry.clone());
+    }
+    (entries, truncated)
+}
@@ -0,0 +1,545 @@
+use crate::debug_log::DebugEntry;
+use crate::host::HostEvent;
+use crate::inputs::{parse_inputs_json, parse_program_inputs};
+use crate::limits::{env_or, run_with_timeout, LIMITS};
+use crate::playground::{
+    classify_execution_error, resolve_source_location, timeout_message, ErrorKind,
+    ExecutionSettings, ModuleSource, SourceLocation, PLAYGROUND,
+};
+use crate::progress::{interruptible, Progress};
+use miden_vm::{
+    assembly::DefaultSourceManager, execute, execute_iter, AdviceInputs, ExecutionError,
+    StackInputs, VmState, VmStateIterator,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+const MAX_DEBUG_SESSIONS_ENV: &str = "MASM_PLAYGROUND_MAX_DEBUG_SESSIONS";
+
+/// Number of debug sessions kept alive at once unless configured otherwise; the oldest session is
+/// dropped first.
+const DEFAULT_MAX_DEBUG_SESSIONS: usize = 32;
+
+/// Number of visited states a session keeps for stepping back. Each state holds a copy of the
+/// memory, so older states are dropped rather than kept for the whole run.
+const MAX_HISTORY: usize = 1024;
+
+static MAX_DEBUG_SESSIONS: Lazy<usize> =
+    Lazy::new(|| env_or(MAX_DEBUG_SESSIONS_ENV, DEFAULT_MAX_DEBUG_SESSIONS).max(1));
+
+/// Each session has a lock of its own, so stepping through one does not block the others.
+static DEBUG_SESSIONS: Lazy<Mutex<BTreeMap<u64, Arc<Mutex<DebugSession>>>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Deserialize)]
+pub struct DebugStartRequest {
+    pub program: String,
+    pub inputs: Option<Value>,
+    #[serde(default)]
+    pub modules: Vec<ModuleSource>,
+    /// One-indexed source lines at which `continue` should stop.
+    pub breakpoints: Option<Vec<u32>>,
+    /// Event handlers, output format and limits, as for execution.
+    #[serde(default)]
+    pub settings: ExecutionSettings,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugCommand {
+    /// Advance by a single clock cycle.
+    Step,
+    /// Advance to the next instruction in the current procedure, running any callee to completion.
+    StepOver,
+    /// Advance until a `breakpoint` instruction or a breakpoint line is reached.
+    #[serde(alias = "continue_to_breakpoint")]
+    Continue,
+    /// Go back by a single clock cycle, up to the last [MAX_HISTORY] states visited.
+    Back,
+}
+
+#[derive(Serialize)]
+pub struct MemoryCell {
+    pub address: u32,
+    pub value: String,
+}
+
+#[derive(Serialize)]
+pub struct DebugState {
+    pub clk: u32,
+    pub context: u32,
+    pub operation: Option<String>,
+    pub instruction: Option<String>,
+    pub procedure: Option<String>,
+    pub stack: Vec<String>,
+    pub memory: Vec<MemoryCell>,
+    pub source_location: Option<SourceLocation>,
+}
+
+#[derive(Serialize)]
+pub struct DebugResult {
+    pub success: bool,
+    pub session_id: Option<u64>,
+    pub state: Option<DebugState>,
+    pub finished: bool,
+    pub error: Option<String>,
+    /// Events the program emits, only returned when the session starts; the whole program runs
+    /// then, so these cover the entire run.
+    pub events: Vec<HostEvent>,
+    pub events_truncated: bool,
+    /// Output of the program's `debug` and `trace` decorators, only returned when the session
+    /// starts.
+    pub debug_log: Vec<DebugEntry>,
+    pub debug_log_truncated: bool,
+}
+
+/// A VM state together with the procedure call depth at which it was reached.
+struct TracedState {
+    state: VmState,
+    depth: usize,
+}
+
+struct DebugSession {
+    states: VmStateIterator,
+    source_manager: Arc<DefaultSourceManager>,
+    /// The most recently visited states, so stepping back does not require re-execution.
+    history: VecDeque<TracedState>,
+    position: usize,
+    /// Procedure names making up the call stack at the last state in `history`.
+    frames: Vec<String>,
+    breakpoints: Vec<u32>,
+    finished: bool,
+    error: Option<String>,
+}
+
+impl DebugSession {
+    fn current(&self) -> &TracedState {
+        &self.history[self.position]
+    }
+
+    /// Moves one clock cycle forward, returning false once the end of execution is reached.
+    fn advance(&mut self) -> bool {
+        if self.position + 1 < self.history.len() {
+            self.position += 1;
+            return true;
+        }
+
+        if self.finished {
+            return false;
+        }
+
+        match self.states.next() {
+            Some(Ok(state)) => {
+                self.record(state);
+                self.position = self.history.len() - 1;
+                true
+            }
+            Some(Err(e)) => {
+                self.finished = true;
+                self.error = Some(format!("Execution error: {e}"));
+                false
+            }
+            None => {
+                self.finished = true;
+                false
+            }
+        }
+    }
+
+    fn record(&mut self, state: VmState) {
+        if let Some(procedure) = instruction_start(&state) {
+            // MASM has no recursion, so returning to a procedure already on the call stack
+            // means every frame above it has completed
+            match self.frames.iter().rposition(|frame| frame == procedure) {
+                Some(idx) => self.frames.truncate(idx + 1),
+                None => self.frames.push(procedure.to_string()),
+            }
+        }
+
+        if self.history.len() == MAX_HISTORY {
+            self.history.pop_front();
+            self.position = self.position.saturating_sub(1);
+        }
+        self.history.push_back(TracedState {
+            state,
+            depth: self.frames.len(),
+        });
+    }
+
+    fn source_line(&self, state: &VmState) -> Option<u32> {
+        let location = state.asmop.as_ref()?.as_ref().location()?;
+        resolve_source_location(&self.source_manager, location).map(|loc| loc.line)
+    }
+
+    fn is_breakpoint(&self, state: &VmState) -> bool {
+        let Some(asmop) = &state.asmop else {
+            return false;
+        };
+        if instruction_start(state).is_none() {
+            return false;
+        }
+        if asmop.should_break() {
+            return true;
+        }
+
+        self.source_line(state)
+            .is_some_and(|line| self.breakpoints.contains(&line))
+    }
+
+    fn run(&mut self, command: DebugCommand) {
+        match command {
+            DebugCommand::Step => {
+                self.advance();
+            }
+            DebugCommand::StepOver => {
+                let start_depth = self.current().depth;
+                while self.advance() {
+                    let current = self.current();
+                    if instruction_start(&current.state).is_some() && current.depth <= start_depth {
+                        break;
+                    }
+                }
+            }
+            DebugCommand::Continue => {
+                // Only the state the session is stopped at is skipped, so a breakpoint inside a
+                // loop is hit again on the next iteration
+                while self.advance() {
+                    if self.is_breakpoint(&self.current().state) {
+                        break;
+                    }
+                }
+            }
+            DebugCommand::Back => {
+                self.position = self.position.saturating_sub(1);
+            }
+        }
+    }
+
+    fn result(&self, session_id: u64) -> DebugResult {
+        let state = &self.current().state;
+        let at_end = self.finished && self.position + 1 == self.history.len();
+
+        DebugResult {
+            success: true,
+            session_id: Some(session_id),
+            state: Some(DebugState {
+                clk: state.clk.as_u32(),
+                context: state.ctx.into(),
+                operation: state.op.map(|op| op.to_string()),
+                instruction: state.asmop.as_ref().map(|asmop| asmop.op().to_string()),
+                procedure: state
+                    .asmop
+                    .as_ref()
+                    .map(|asmop| asmop.context_name().to_string()),
+                stack: state.stack.iter().map(|f| f.to_string()).collect(),
+                memory: state
+                    .memory
+                    .iter()
+                    .map(|(addr, value)| MemoryCell {
+                        address: (*addr).into(),
+                        value: value.to_string(),
+                    })
+                    .collect(),
+                source_location: state
+                    .asmop
+                    .as_ref()
+                    .and_then(|asmop| asmop.as_ref().location())
+                    .and_then(|loc| resolve_source_location(&self.source_manager, loc)),
+            }),
+            finished: at_end,
+            error: if at_end { self.error.clone() } else { None },
+            events: Vec::new(),
+            events_truncated: false,
+            debug_log: Vec::new(),
+            debug_log_truncated: false,
+        }
+    }
+}
+
+/// Returns the procedure name if this state is the first cycle of an assembly instruction.
+fn instruction_start(state: &VmState) -> Option<&str> {
+    state
+        .asmop
+        .as_ref()
+        .filter(|asmop| asmop.cycle_idx() == 1)
+        .map(|asmop| asmop.context_name())
+}
+
+fn failure(session_id: Option<u64>, error: String) -> DebugResult {
+    DebugResult {
+        success: false,
+        session_id,
+        state: None,
+        finished: false,
+        error: Some(error),
+        events: Vec::new(),
+        events_truncated: false,
+        debug_log: Vec::new(),
+        debug_log_truncated: false,
+    }
+}
+
+#[tauri::command]
+pub fn start_debug_session(
+    program: &str,
+    inputs_json: Option<String>,
+    modules: Option<Vec<ModuleSource>>,
+    breakpoints: Option<Vec<u32>>,
+    settings: Option<ExecutionSettings>,
+) -> Result<String, String> {
+    let inputs = match inputs_json.as_deref().map(parse_inputs_json).transpose() {
+        Ok(inputs) => inputs,
+        Err(e) => return Ok(serde_json::to_string(&failure(None, e)).unwrap()),
+    };
+    let result = start_debug_session_impl(
+        program,
+        inputs.as_ref(),
+        &modules.unwrap_or_default(),
+        breakpoints.unwrap_or_default(),
+        &settings.unwrap_or_default(),
+    );
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+#[tauri::command]
+pub fn debug_command(session_id: u64, command: DebugCommand) -> Result<String, String> {
+    let result = debug_command_impl(session_id, command);
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+#[tauri::command]
+pub fn end_debug_session(session_id: u64) -> Result<String, String> {
+    let result = end_debug_session_impl(session_id);
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+pub fn start_debug_session_impl(
+    program: &str,
+    inputs: Option<&Value>,
+    modules: &[ModuleSource],
+    breakpoints: Vec<u32>,
+    settings: &ExecutionSettings,
+) -> DebugResult {
+    let source_manager = Arc::new(DefaultSourceManager::default());
+
+    let assembler = match PLAYGROUND.assembler(&source_manager, modules) {
+        Ok(assembler) => assembler,
+        Err(e) => return failure(None, e.message),
+    };
+
+    let (stack_inputs, advice_inputs) = if let Some(inputs) = inputs {
+        match parse_program_inputs(inputs) {
+            Ok(inputs) => inputs,
+            Err(e) => return failure(None, e),
+        }
+    } else {
+        (StackInputs::default(), AdviceInputs::default())
+    };
+
+    let program = match assembler.assemble_program(program) {
+        Ok(prog) => prog,
+        Err(e) => return failure(None, format!("Assembly error: {e}")),
+    };
+
+    // `execute_iter` has no cycle limit, so make sure the program terminates within the server
+    // limits first. Other execution errors are left for the session to step up to.
+    let limits = LIMITS.resolve(&settings.limits);
+    let exec_options = match limits.execution_options() {
+        Ok(options) => options,
+        Err(e) => return failure(None, e),
+    };
+    let mut host = match PLAYGROUND.event_host(settings) {
+        Ok(host) => host,
+        Err(e) => return failure(None, e),
+    };
+    let bounded_run = {
+        let program = program.clone();
+        let stack_inputs = stack_inputs.clone();
+        let advice_inputs = advice_inputs.clone();
+        let source_manager = source_manager.clone();
+        let progress = Progress::default();
+        let mut host = host.clone().with_progress(&progress);
+        run_with_timeout(limits.timeout, &progress, move || {
+            execute(
+                &interruptible(&program),
+                stack_inputs,
+                advice_inputs,
+                &mut host,
+                exec_options,
+                source_manager,
+            )
+            .map(|_| ())
+        })
+    };
+    match bounded_run {
+        Some(Err(e @ ExecutionError::CycleLimitExceeded(_))) => {
+            return failure(None, classify_execution_error(&e, ErrorKind::Execution).0)
+        }
+        None => return failure(None, timeout_message("execution", &limits)),
+        Some(_) => {}
+    }
+
+    // The whole program is executed up front; the iterator then replays the recorded states
+    let states = execute_iter(
+        &program,
+        stack_inputs,
+        advice_inputs,
+        &mut host,
+        source_manager.clone(),
+    );
+
+    let mut session = DebugSession {
+        states,
+        source_manager,
+        history: VecDeque::new(),
+        position: 0,
+        frames: Vec::new(),
+        breakpoints,
+        finished: false,
+        error: None,
+    };
+
+    match session.states.next() {
+        Some(Ok(state)) => session.record(state),
+        Some(Err(e)) => return failure(None, format!("Execution error: {e}")),
+        None => return failure(None, "Execution produced no state".to_string()),
+    }
+
+    let session_id = NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+    let mut result = session.result(session_id);
+    (result.events, result.events_truncated) = host.clone().into_events();
+    (result.debug_log, result.debug_log_truncated) = host.into_debug_log();
+
+    let mut sessions = DEBUG_SESSIONS.lock().unwrap();
+    while sessions.len() >= *MAX_DEBUG_SESSIONS {
+        sessions.pop_first();
+    }
+    sessions.insert(session_id, Arc::new(Mutex::new(session)));
+
+    result
+}
+
+pub fn debug_command_impl(session_id: u64, command: DebugCommand) -> DebugResult {
+    let session = DEBUG_SESSIONS.lock().unwrap().get(&session_id).cloned();
+    let Some(session) = session else {
+        return failure(
+            Some(session_id),
+            format!("Unknown debug session {session_id}"),
+        );
+    };
+
+    let mut session = session.lock().unwrap();
+    session.run(command);
+    session.result(session_id)
+}
+
+pub fn end_debug_session_impl(session_id: u64) -> DebugResult {
+    match DEBUG_SESSIONS.lock().unwrap().remove(&session_id) {
+        Some(_) => DebugResult {
+            success: true,
+            session_id: Some(session_id),
+            state: None,
+            finished: true,
+            error: None,
+            events: Vec::new(),
+            events_truncated: false,
+            debug_log: Vec::new(),
+            debug_log_truncated: false,
+        },
+        None => failure(
+            Some(session_id),
+            format!("Unknown debug session {session_id}"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str =
+        "proc.inc\n    push.1\n    add\nend\n\nbegin\n    push.5\n    exec.inc\n    push.7\nend\n";
+
+    fn start(program: &str, breakpoints: Vec<u32>) -> u64 {
+        let result = start_debug_session_impl(program, None, &[], breakpoints, &Default::default());
+        assert!(result.success, "{:?}", result.error);
+        result.session_id.unwrap()
+    }
+
+    fn run(session_id: u64, command: DebugCommand) -> (DebugState, bool) {
+        let result = debug_command_impl(session_id, command);
+        assert!(result.success, "{:?}", result.error);
+        (result.state.unwrap(), result.finished)
+    }
+
+    fn line(state: &DebugState) -> Option<u32> {
+        state.source_location.as_ref().map(|location| location.line)
+    }
+
+    #[test]
+    fn step_and_back_move_one_cycle() {
+        let id = start(PROGRAM, Vec::new());
+        assert_eq!(run(id, DebugCommand::Step).0.clk, 1);
+        assert_eq!(run(id, DebugCommand::Step).0.clk, 2);
+        assert_eq!(run(id, DebugCommand::Back).0.clk, 1);
+        assert_eq!(run(id, DebugCommand::Back).0.clk, 0);
+        assert_eq!(run(id, DebugCommand::Back).0.clk, 0);
+        assert_eq!(run(id, DebugCommand::Step).0.clk, 1);
+    }
+
+    #[test]
+    fn continue_stops_at_breakpoints_and_step_over_skips_calls() {
+        let id = start(PROGRAM, vec![7]);
+        let (state, _) = run(id, DebugCommand::Continue);
+        assert_eq!(line(&state), Some(7));
+        assert_eq!(state.instruction.as_deref(), Some("push.5"));
+
+        // The procedure runs to completion, leaving 5 + 1 below the next push
+        let (state, _) = run(id, DebugCommand::StepOver);
+        assert_eq!(line(&state), Some(9));
+        assert_eq!(state.stack[..2], ["7", "6"]);
+
+        let (_, finished) = run(id, DebugCommand::Continue);
+        assert!(finished);
+        let (state, finished) = run(id, DebugCommand::Back);
+        assert!(!finished);
+        assert!(state.clk > 0);
+    }
+
+    #[test]
+    fn breakpoints_in_loops_stop_on_every_iteration() {
+        let program = "begin\n    push.3\n    push.1\n    while.true\n        add.1\n        dup neq.6\n    end\nend";
+        let id = start(program, vec![5]);
+        let mut hits = Vec::new();
+        loop {
+            let (state, finished) = run(id, DebugCommand::Continue);
+            if finished {
+                break;
+            }
+            assert_eq!(line(&state), Some(5));
+            hits.push(state.clk);
+        }
+        // The loop runs while the counter goes from 3 to 6
+        assert_eq!(hits.len(), 3);
+        assert!(hits.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn back_is_limited_to_the_recent_history() {
+        let id = start("begin repeat.3000 push.1 drop end end", Vec::new());
+        for _ in 0..2000 {
+            run(id, DebugCommand::Step);
+        }
+        let mut clk = 0;
+        for _ in 0..1500 {
+            clk = run(id, DebugCommand::Back).0.clk;
+        }
+        assert_eq!(clk as usize, 2000 - (MAX_HISTORY - 1));
+    }
+}
@@ -0,0 +1,101 @@
+use miden_vm::diagnostics::{Diagnostic as MietteDiagnostic, LabeledSpan, Severity, SourceCode};
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct DiagnosticLabel {
+    pub message: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub span_length: usize,
+}
+
+/// An assembly or execution error in a form the editor can use to underline the offending source.
+///
+/// `line` and `column` are one-indexed and point at the primary label of the error, if it has one.
+#[derive(Serialize)]
+pub struct Diagnostic {
+    pub severity: String,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub column: Option<u32>,
+    pub span_length: Option<usize>,
+    pub help: Option<String>,
+    pub labels: Vec<DiagnosticLabel>,
+}
+
+/// Flattens a diagnostic and all of its related diagnostics into a list of [Diagnostic]s.
+pub fn collect_diagnostics(diagnostic: &dyn MietteDiagnostic) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    collect_into(diagnostic, None, &mut diagnostics);
+    diagnostics
+}
+
+fn collect_into<'a>(
+    diagnostic: &'a dyn MietteDiagnostic,
+    parent_source: Option<&'a dyn SourceCode>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    // Related diagnostics often omit their source code and rely on the parent's
+    let source = diagnostic.source_code().or(parent_source);
+
+    let labels: Vec<DiagnosticLabel> = diagnostic
+        .labels()
+        .into_iter()
+        .flatten()
+        .map(|span| resolve_label(&span, source))
+        .collect();
+
+    let primary_idx = diagnostic
+        .labels()
+        .into_iter()
+        .flatten()
+        .position(|span| span.primary())
+        .unwrap_or(0);
+    let primary = labels.get(primary_idx);
+
+    diagnostics.push(Diagnostic {
+        severity: severity_name(diagnostic.severity()).to_string(),
+        message: diagnostic.to_string(),
+        file: primary.and_then(|label| label.file.clone()),
+        line: primary.and_then(|label| label.line),
+        column: primary.and_then(|label| label.column),
+        span_length: primary.map(|label| label.span_length),
+        help: diagnostic.help().map(|help| help.to_string()),
+        labels,
+    });
+
+    for related in diagnostic.related().into_iter().flatten() {
+        collect_into(related, source, diagnostics);
+    }
+
+    if let Some(inner) = diagnostic.diagnostic_source() {
+        collect_into(inner, source, diagnostics);
+    }
+}
+
+fn resolve_label(span: &LabeledSpan, source: Option<&dyn SourceCode>) -> DiagnosticLabel {
+    let contents = source.and_then(|source| source.read_span(span.inner(), 0, 0).ok());
+
+    DiagnosticLabel {
+        message: span.label().map(|label| label.to_string()),
+        file: contents
+            .as_ref()
+            .and_then(|contents| contents.name().map(|name| name.to_string())),
+        // miette reports zero-indexed positions, editors expect one-indexed ones
+        line: contents.as_ref().map(|contents| contents.line() as u32 + 1),
+        column: contents
+            .as_ref()
+            .map(|contents| contents.column() as u32 + 1),
+        span_length: span.len(),
+    }
+}
+
+fn severity_name(severity: Option<Severity>) -> &'static str {
+    match severity.unwrap_or(Severity::Error) {
+        Severity::Advice => "advice",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
@@ -0,0 +1,183 @@
+use crate::diagnostics::Diagnostic;
+use crate::mast_tree::{children, procedure_root_name};
+use crate::playground::{resolve_source_location, ErrorKind, ModuleSource, SourceLocation};
+use miden_core::{
+    mast::{MastForest, MastNodeId},
+    Decorator,
+};
+use miden_vm::{assembly::DefaultSourceManager, Program, Word};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Deserialize)]
+pub struct DisassembleRequest {
+    #[serde(default)]
+    pub program: String,
+    /// A precompiled program, as returned by `/api/export`, disassembled instead of `program`.
+    pub mast: Option<Vec<u8>>,
+    #[serde(default)]
+    pub modules: Vec<ModuleSource>,
+}
+
+/// A source instruction and the VM operations it was lowered to.
+#[derive(Serialize)]
+pub struct DisassembledInstruction {
+    /// The MASM instruction, or `None` for operations without debug information, such as library
+    /// code inlined into the block.
+    pub instruction: Option<String>,
+    /// The procedure the instruction was written in.
+    pub procedure: Option<String>,
+    pub location: Option<SourceLocation>,
+    pub operations: Vec<String>,
+    /// Cycles the operations take, one per operation.
+    pub cycles: u32,
+}
+
+#[derive(Serialize)]
+pub struct DisassembledBlock {
+    /// Index of the basic block in the MAST forest.
+    pub id: u32,
+    pub digest: String,
+    /// The procedure the block is the root of, if any.
+    pub procedure: Option<String>,
+    pub instructions: Vec<DisassembledInstruction>,
+    /// Cycles to run the whole block: its operations plus the SPAN, RESPAN and END operations
+    /// that open its batches and close it.
+    pub cycles: u32,
+}
+
+#[derive(Serialize)]
+pub struct DisassemblyResult {
+    pub success: bool,
+    pub program_hash: Option<String>,
+    /// Every basic block reachable from the entrypoint, in program order. A block shared by
+    /// several callers is listed once.
+    pub blocks: Vec<DisassembledBlock>,
+    pub error: Option<String>,
+    pub error_kind: Option<ErrorKind>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl DisassemblyResult {
+    pub fn failure(error_kind: ErrorKind, error: String, diagnostics: Vec<Diagnostic>) -> Self {
+        DisassemblyResult {
+            success: false,
+            program_hash: None,
+            blocks: Vec::new(),
+            error: Some(error),
+            error_kind: Some(error_kind),
+            diagnostics,
+        }
+    }
+}
+
+/// Splits a basic block into the instructions its operations came from.
+fn disassemble_block(
+    forest: &MastForest,
+    node_id: MastNodeId,
+    source_manager: &DefaultSourceManager,
+) -> Vec<DisassembledInstruction> {
+    let Some(block) = forest[node_id].get_basic_block() else {
+        return Vec::new();
+    };
+    let operations: Vec<String> = block.operations().map(|op| op.to_string()).collect();
+
+    // An instruction is recorded against the first of its operations
+    let mut asmops = block
+        .decorators()
+        .iter()
+        .filter_map(|&(index, id)| match &forest[id] {
+            Decorator::AsmOp(asmop) => Some((index, asmop)),
+            _ => None,
+        })
+        .peekable();
+
+    let mut instructions = Vec::new();
+    let mut unattributed: Vec<String> = Vec::new();
+    let mut index = 0;
+    while index < operations.len() {
+        let Some((_, asmop)) = asmops.next_if(|&(start, _)| start <= index) else {
+            unattributed.push(operations[index].clone());
+            index += 1;
+            continue;
+        };
+        if !unattributed.is_empty() {
+            instructions.push(unattributed_instruction(std::mem::take(&mut unattributed)));
+        }
+
+        // Stop at the next instruction in case the cycle count overstates this one
+        let next = asmops
+            .peek()
+            .map_or(operations.len(), |&(next, _)| next.min(operations.len()));
+        let end = (index + asmop.num_cycles() as usize).min(next).max(index);
+        instructions.push(DisassembledInstruction {
+            instruction: Some(asmop.op().to_string()),
+            procedure: Some(asmop.context_name().to_string()),
+            location: asmop
+                .location()
+                .and_then(|location| resolve_source_location(source_manager, location)),
+            operations: operations[index..end].to_vec(),
+            cycles: (end - index) as u32,
+        });
+        index = end;
+    }
+    if !unattributed.is_empty() {
+        instructions.push(unattributed_instruction(unattributed));
+    }
+    instructions
+}
+
+fn unattributed_instruction(operations: Vec<String>) -> DisassembledInstruction {
+    DisassembledInstruction {
+        instruction: None,
+        procedure: None,
+        location: None,
+        cycles: operations.len() as u32,
+        operations,
+    }
+}
+
+/// Disassembles every basic block reachable from the entrypoint of `program`.
+pub fn disassemble_program(
+    program: &Program,
+    source_manager: &DefaultSourceManager,
+    library_procedures: &HashMap<Word, String>,
+) -> DisassemblyResult {
+    let forest = program.mast_forest();
+    let mut blocks = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![program.entrypoint()];
+
+    while let Some(node_id) = pending.pop() {
+        if !visited.insert(node_id) {
+            continue;
+        }
+        let node = &forest[node_id];
+        if let Some(block) = node.get_basic_block() {
+            blocks.push(DisassembledBlock {
+                id: node_id.as_u32(),
+                digest: node.digest().to_string(),
+                procedure: procedure_root_name(
+                    forest,
+                    node_id,
+                    program.entrypoint(),
+                    library_procedures,
+                ),
+                instructions: disassemble_block(forest, node_id, source_manager),
+                cycles: block.num_operations() + block.num_op_batches() as u32 + 1,
+            });
+        }
+
+        // Children are pushed in reverse so blocks come out in program order
+        pending.extend(children(node).into_iter().rev());
+    }
+
+    DisassemblyResult {
+        success: true,
+        program_hash: Some(program.hash().to_string()),
+        blocks,
+        error: None,
+        error_kind: None,
+        diagnostics: Vec::new(),
+    }
+}
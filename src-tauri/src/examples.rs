@@ -0,0 +1,260 @@
+use crate::playground::{ExecutionRequest, Playground};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the directory the example catalog is loaded from.
+const EXAMPLES_DIR_ENV: &str = "MASM_PLAYGROUND_EXAMPLES_DIR";
+/// Lists the examples in the catalog directory; each one has its source in `<id>.masm`.
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// The catalog in `examples/`, compiled in so that it ships with the app.
+const BUNDLED_MANIFEST: &str = include_str!("../examples/manifest.json");
+const BUNDLED_SOURCES: &[(&str, &str)] = &[
+    (
+        "basic-addition",
+        include_str!("../examples/basic-addition.masm"),
+    ),
+    (
+        "input-stack-demo",
+        include_str!("../examples/input-stack-demo.masm"),
+    ),
+    ("fibonacci", include_str!("../examples/fibonacci.masm")),
+    (
+        "prime-generator",
+        include_str!("../examples/prime-generator.masm"),
+    ),
+    (
+        "conditional-logic",
+        include_str!("../examples/conditional-logic.masm"),
+    ),
+    (
+        "memory-operations",
+        include_str!("../examples/memory-operations.masm"),
+    ),
+    (
+        "stack-manipulation",
+        include_str!("../examples/stack-manipulation.masm"),
+    ),
+    (
+        "counter-with-input",
+        include_str!("../examples/counter-with-input.masm"),
+    ),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    Beginner,
+    Intermediate,
+    Advanced,
+}
+
+/// An entry of the catalog manifest.
+#[derive(Deserialize)]
+struct ManifestEntry {
+    id: String,
+    title: String,
+    description: String,
+    category: String,
+    difficulty: Difficulty,
+    inputs: Option<Value>,
+    #[serde(default)]
+    expected_stack: Vec<String>,
+}
+
+#[derive(Serialize, Clone)]
+pub struct Example {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub category: String,
+    pub difficulty: Difficulty,
+    pub source: String,
+    /// Inputs the example is meant to be run with, in the same format as execution requests.
+    pub inputs: Option<Value>,
+    /// The top of the output stack when the example is run with its inputs.
+    pub expected_stack: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct ExampleResult {
+    pub success: bool,
+    pub example: Option<Example>,
+    pub examples: Option<Vec<Example>>,
+    pub error: Option<String>,
+}
+
+/// The example programs shipped with the playground.
+#[derive(Default)]
+pub struct ExampleCatalog {
+    examples: Vec<Example>,
+    /// Problems found while loading the catalog, such as a missing source file.
+    errors: Vec<String>,
+}
+
+impl ExampleCatalog {
+    /// Loads the catalog from the directory named by `MASM_PLAYGROUND_EXAMPLES_DIR`, falling back
+    /// to the [bundled](ExampleCatalog::bundled) examples.
+    pub fn from_env() -> Self {
+        match std::env::var_os(EXAMPLES_DIR_ENV) {
+            Some(dir) => Self::load(&PathBuf::from(dir)),
+            None => Self::bundled(),
+        }
+    }
+
+    /// The examples compiled into the app.
+    pub fn bundled() -> Self {
+        Self::from_manifest(
+            "bundled example manifest",
+            Ok(BUNDLED_MANIFEST.to_string()),
+            |id| {
+                BUNDLED_SOURCES
+                    .iter()
+                    .find(|(source_id, _)| *source_id == id)
+                    .map(|(_, source)| source.to_string())
+                    .ok_or_else(|| format!("Bundled example {id} has no source"))
+            },
+        )
+    }
+
+    /// Loads every example listed in the manifest of `dir`. Examples that cannot be read are
+    /// skipped and reported through [ExampleCatalog::errors].
+    pub fn load(dir: &Path) -> Self {
+        let manifest_path = dir.join(MANIFEST_FILE);
+        Self::from_manifest(
+            &format!("example manifest {}", manifest_path.display()),
+            fs::read_to_string(&manifest_path).map_err(|e| e.to_string()),
+            |id| {
+                let source_path = dir.join(format!("{id}.masm"));
+                fs::read_to_string(&source_path)
+                    .map_err(|e| format!("Failed to read example {}: {e}", source_path.display()))
+            },
+        )
+    }
+
+    fn from_manifest(
+        manifest_name: &str,
+        manifest: Result<String, String>,
+        read_source: impl Fn(&str) -> Result<String, String>,
+    ) -> Self {
+        let manifest: Vec<ManifestEntry> = match manifest
+            .and_then(|manifest| serde_json::from_str(&manifest).map_err(|e| e.to_string()))
+        {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                return ExampleCatalog {
+                    examples: Vec::new(),
+                    errors: vec![format!("Failed to read {manifest_name}: {e}")],
+                }
+            }
+        };
+
+        let mut catalog = ExampleCatalog::default();
+        for entry in manifest {
+            if catalog.get(&entry.id).is_some() {
+                catalog
+                    .errors
+                    .push(format!("Duplicate example id '{}'", entry.id));
+                continue;
+            }
+            let source = match read_source(&entry.id) {
+                Ok(source) => source,
+                Err(e) => {
+                    catalog.errors.push(e);
+                    continue;
+                }
+            };
+            catalog.examples.push(Example {
+                id: entry.id,
+                title: entry.title,
+                description: entry.description,
+                category: entry.category,
+                difficulty: entry.difficulty,
+                source,
+                inputs: entry.inputs,
+                expected_stack: entry.expected_stack,
+            });
+        }
+        catalog
+    }
+
+    pub fn examples(&self) -> &[Example] {
+        &self.examples
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Example> {
+        self.examples.iter().find(|example| example.id == id)
+    }
+
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// Runs every example with its default inputs, returning a description of each example that
+    /// fails or does not produce its expected stack.
+    pub fn validate(&self, playground: &Playground) -> Vec<String> {
+        let mut problems = self.errors.clone();
+        for example in &self.examples {
+            let result = playground.execute(&ExecutionRequest {
+                program: example.source.clone(),
+                inputs: example.inputs.clone(),
+                ..Default::default()
+            });
+            let stack = result.stack_outputs.unwrap_or_default();
+            if !result.success {
+                problems.push(format!(
+                    "Example '{}' failed: {}",
+                    example.id,
+                    result.error.unwrap_or_default()
+                ));
+            } else if !stack.starts_with(&example.expected_stack) {
+                problems.push(format!(
+                    "Example '{}' left {:?} on the stack, expected {:?}",
+                    example.id,
+                    &stack[..example.expected_stack.len().min(stack.len())],
+                    example.expected_stack
+                ));
+            }
+        }
+        problems
+    }
+
+    pub fn list(&self) -> ExampleResult {
+        ExampleResult {
+            success: true,
+            example: None,
+            examples: Some(self.examples.clone()),
+            error: None,
+        }
+    }
+
+    pub fn find(&self, id: &str) -> ExampleResult {
+        match self.get(id) {
+            Some(example) => ExampleResult {
+                success: true,
+                example: Some(example.clone()),
+                examples: None,
+                error: None,
+            },
+            None => ExampleResult {
+                success: false,
+                example: None,
+                examples: None,
+                error: Some(format!("Unknown example '{id}'")),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::playground::PLAYGROUND;
+
+    #[test]
+    fn examples_run_and_leave_their_expected_stacks() {
+        let problems = PLAYGROUND.validate_examples();
+        assert!(problems.is_empty(), "{problems:#?}");
+    }
+}
@@ -0,0 +1,235 @@
+use crate::debug_log::{capture_debug, capture_trace, DebugEntry, MAX_DEBUG_ENTRIES};
+use crate::inputs::parse_felt;
+use crate::outputs::{format_felt, format_stack, OutputSettings};
+use crate::progress::{Progress, CANCEL_CHECK_TRACE_ID};
+use miden_core::{mast::MastForest, DebugOptions, Felt};
+use miden_processor::{ErrorContext, ProcessState};
+use miden_vm::{BaseHost, DefaultHost, ExecutionError, SyncHost, Word};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Number of elements a snapshot keeps unless the handler asks for the whole stack.
+const SNAPSHOT_DEPTH: usize = 16;
+
+/// Maximum number of events recorded per run; an `emit` inside a loop fires on every iteration.
+const MAX_EVENTS: usize = 1024;
+
+/// What the host does when the program emits an event with `emit.<event_id>`.
+#[derive(Deserialize, Clone)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum EventAction {
+    /// Records the whole stack, including the elements below the top 16.
+    LogStack,
+    /// Pushes `values` onto the advice stack, the first value ending up on top, so the program
+    /// reads them in order with `adv_push`. Values are pushed again each time the event fires.
+    PushAdvice { values: Vec<Value> },
+    /// Records `label`, to mark points of interest in the event log.
+    Marker { label: String },
+}
+
+/// Registers a handler for one event ID.
+#[derive(Deserialize, Clone)]
+pub struct EventHandler {
+    pub event_id: u32,
+    #[serde(flatten)]
+    pub action: EventAction,
+}
+
+/// An event the program emitted while it ran.
+#[derive(Serialize, Clone)]
+pub struct HostEvent {
+    pub event_id: u32,
+    /// The cycle the event was emitted in.
+    pub cycle: u32,
+    /// `log_stack`, `push_advice` or `marker`; `None` for events without a handler.
+    pub action: Option<String>,
+    pub label: Option<String>,
+    /// Values pushed onto the advice stack, top first.
+    pub advice: Vec<String>,
+    /// The stack when the event was emitted, top first: the top 16 elements, or the whole stack
+    /// for `log_stack`.
+    pub stack: Vec<String>,
+}
+
+#[derive(Clone)]
+enum Handler {
+    LogStack,
+    PushAdvice(Vec<Felt>),
+    Marker(String),
+}
+
+/// A host that resolves the playground libraries, runs the handlers registered by the request and
+/// records every event the program emits, along with the output of its debug decorators.
+#[derive(Clone)]
+pub struct PlaygroundHost {
+    libraries: DefaultHost,
+    handlers: HashMap<u32, Handler>,
+    output: OutputSettings,
+    events: Vec<HostEvent>,
+    events_truncated: bool,
+    debug_log: Vec<DebugEntry>,
+    debug_log_truncated: bool,
+    progress: Progress,
+}
+
+impl PlaygroundHost {
+    /// Wraps `libraries`, checking the handlers up front so bad values are reported as input
+    /// errors rather than in the middle of execution.
+    pub fn new(
+        libraries: DefaultHost,
+        handlers: &[EventHandler],
+        output: &OutputSettings,
+    ) -> Result<Self, String> {
+        let mut parsed = HashMap::with_capacity(handlers.len());
+        for (index, handler) in handlers.iter().enumerate() {
+            let event_id = handler.event_id;
+            let field = format!("events[{index}].values");
+            let action = match &handler.action {
+                EventAction::LogStack => Handler::LogStack,
+                EventAction::PushAdvice { values } => Handler::PushAdvice(
+                    values
+                        .iter()
+                        .enumerate()
+                        .map(|(i, value)| parse_felt(value, &format!("{field}[{i}]")))
+                        .collect::<Result<_, _>>()?,
+                ),
+                EventAction::Marker { label } => Handler::Marker(label.clone()),
+            };
+            if parsed.insert(event_id, action).is_some() {
+                return Err(format!("Event {event_id} has more than one handler"));
+            }
+        }
+
+        Ok(PlaygroundHost {
+            libraries,
+            handlers: parsed,
+            output: output.clone(),
+            events: Vec::new(),
+            events_truncated: false,
+            debug_log: Vec::new(),
+            debug_log_truncated: false,
+            progress: Progress::default(),
+        })
+    }
+
+    /// Stops execution with an error once `progress` is cancelled. Only
+    /// [interruptible](crate::progress::interruptible) programs give the host a chance to check.
+    pub fn with_progress(mut self, progress: &Progress) -> Self {
+        self.progress = progress.clone();
+        self
+    }
+
+    /// The events emitted so far, in order, and whether any was left out past the first
+    /// [MAX_EVENTS]. Handlers keep running for the events that are left out.
+    pub fn into_events(self) -> (Vec<HostEvent>, bool) {
+        (self.events, self.events_truncated)
+    }
+
+    /// The output of the `debug` and `trace` decorators run so far, which the VM only runs in debug
+    /// mode, and whether any was left out past the first [MAX_DEBUG_ENTRIES]; see
+    /// [crate::debug_log::debug_log].
+    pub fn into_debug_log(self) -> (Vec<DebugEntry>, bool) {
+        (self.debug_log, self.debug_log_truncated)
+    }
+
+    /// Whether there is room for another debug entry, noting that one was dropped if not.
+    fn debug_log_has_room(&mut self) -> bool {
+        if self.debug_log.len() < MAX_DEBUG_ENTRIES {
+            return true;
+        }
+        self.debug_log_truncated = true;
+        false
+    }
+}
+
+impl BaseHost for PlaygroundHost {
+    fn on_debug(
+        &mut self,
+        process: &mut ProcessState,
+        options: &DebugOptions,
+    ) -> Result<(), ExecutionError> {
+        if self.debug_log_has_room() {
+            let entry = capture_debug(process, options, self.output.format);
+            self.debug_log.push(entry);
+        }
+        Ok(())
+    }
+
+    fn on_trace(
+        &mut self,
+        process: &mut ProcessState,
+        trace_id: u32,
+    ) -> Result<(), ExecutionError> {
+        if trace_id == CANCEL_CHECK_TRACE_ID {
+            if self.progress.is_cancelled() {
+                return Err(ExecutionError::FailedToExecuteProgram("cancelled"));
+            }
+            return Ok(());
+        }
+        if self.debug_log_has_room() {
+            self.debug_log.push(capture_trace(process, trace_id));
+        }
+        Ok(())
+    }
+}
+
+impl SyncHost for PlaygroundHost {
+    fn get_mast_forest(&self, node_digest: &Word) -> Option<Arc<MastForest>> {
+        self.libraries.get_mast_forest(node_digest)
+    }
+
+    fn on_event(
+        &mut self,
+        process: &mut ProcessState,
+        event_id: u32,
+        _err_ctx: &impl ErrorContext,
+    ) -> Result<(), ExecutionError> {
+        let handler = self.handlers.get(&event_id);
+        if self.events.len() == MAX_EVENTS {
+            self.events_truncated = true;
+            if let Some(Handler::PushAdvice(values)) = handler {
+                for &value in values.iter().rev() {
+                    process.advice_provider_mut().push_stack(value);
+                }
+            }
+            return Ok(());
+        }
+
+        let mut stack = process.get_stack_state();
+        if !matches!(handler, Some(Handler::LogStack)) {
+            stack.truncate(SNAPSHOT_DEPTH);
+        }
+
+        let mut event = HostEvent {
+            event_id,
+            cycle: process.clk().as_u32(),
+            action: None,
+            label: None,
+            advice: Vec::new(),
+            stack: format_stack(&stack, &self.output).elements,
+        };
+        match handler {
+            None => {}
+            Some(Handler::LogStack) => event.action = Some("log_stack".to_string()),
+            Some(Handler::PushAdvice(values)) => {
+                // The advice stack pops from the back, so the first value goes in last
+                for &value in values.iter().rev() {
+                    process.advice_provider_mut().push_stack(value);
+                }
+                event.action = Some("push_advice".to_string());
+                event.advice = values
+                    .iter()
+                    .map(|&value| format_felt(value, self.output.format))
+                    .collect();
+            }
+            Some(Handler::Marker(label)) => {
+                event.action = Some("marker".to_string());
+                event.label = Some(label.clone());
+            }
+        }
+        self.events.push(event);
+        Ok(())
+    }
+}
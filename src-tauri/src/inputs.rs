@@ -0,0 +1,188 @@
+use crate::outputs::parse_formatted_felt;
+use miden_vm::{
+    crypto::{MerkleStore, MerkleTree, SimpleSmt},
+    math::Felt,
+    AdviceInputs, StackInputs, StackOutputs, Word,
+};
+use serde_json::Value;
+
+/// Depth of the sparse Merkle trees built from `sparse_merkle_tree` input entries.
+const SPARSE_MERKLE_TREE_DEPTH: u8 = 64;
+
+/// Parses inputs handed over as a JSON string, as the Tauri commands receive them.
+pub fn parse_inputs_json(inputs_json: &str) -> Result<Value, String> {
+    serde_json::from_str(inputs_json).map_err(|e| format!("Invalid JSON: {e}"))
+}
+
+pub fn parse_stack_inputs(inputs: &Value) -> Result<StackInputs, String> {
+    let mut stack = Vec::new();
+
+    if let Some(operand_stack) = inputs.get("operand_stack") {
+        let stack_array = operand_stack
+            .as_array()
+            .ok_or_else(|| "Invalid operand_stack: expected an array".to_string())?;
+        for (i, item) in stack_array.iter().enumerate() {
+            if let Some(val_str) = item.as_str() {
+                let val: u64 = val_str
+                    .parse()
+                    .map_err(|e| format!("Invalid number '{val_str}': {e}"))?;
+                stack.push(val);
+            } else if let Some(val_num) = item.as_u64() {
+                stack.push(val_num);
+            } else {
+                return Err(format!(
+                    "Invalid operand_stack[{i}]: expected a u64 or numeric string, got {item}"
+                ));
+            }
+        }
+    }
+
+    // Reverse because stack inputs are pushed in reverse order
+    stack.reverse();
+
+    StackInputs::try_from_ints(stack).map_err(|e| format!("Failed to create stack inputs: {e}"))
+}
+
+pub(crate) fn parse_felt(value: &Value, field: &str) -> Result<Felt, String> {
+    let val = if let Some(val_str) = value.as_str() {
+        val_str
+            .parse::<u64>()
+            .map_err(|e| format!("Invalid {field}: '{val_str}' is not a number: {e}"))?
+    } else if let Some(val_num) = value.as_u64() {
+        val_num
+    } else {
+        return Err(format!("Invalid {field}: expected a number, got {value}"));
+    };
+
+    Felt::try_from(val).map_err(|e| format!("Invalid {field}: {e}"))
+}
+
+fn parse_felt_array(value: &Value, field: &str) -> Result<Vec<Felt>, String> {
+    let items = value
+        .as_array()
+        .ok_or_else(|| format!("Invalid {field}: expected an array"))?;
+
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| parse_felt(item, &format!("{field}[{i}]")))
+        .collect()
+}
+
+/// Parses a word given either as a `0x`-prefixed hex string or as an array of four elements.
+fn parse_word(value: &Value, field: &str) -> Result<Word, String> {
+    if let Some(hex) = value.as_str() {
+        return Word::try_from(hex).map_err(|e| format!("Invalid {field}: '{hex}': {e}"));
+    }
+
+    let elements = parse_felt_array(value, field)?;
+    Word::try_from(elements.as_slice()).map_err(|_| {
+        format!(
+            "Invalid {field}: expected 4 elements, got {}",
+            elements.len()
+        )
+    })
+}
+
+fn parse_merkle_store(value: &Value) -> Result<MerkleStore, String> {
+    let entries = value
+        .as_array()
+        .ok_or_else(|| "Invalid merkle_store: expected an array".to_string())?;
+
+    let mut store = MerkleStore::default();
+
+    for (i, entry) in entries.iter().enumerate() {
+        let field = format!("merkle_store[{i}]");
+
+        if let Some(leaves) = entry.get("merkle_tree") {
+            let field = format!("{field}.merkle_tree");
+            let leaves = leaves
+                .as_array()
+                .ok_or_else(|| format!("Invalid {field}: expected an array of leaves"))?
+                .iter()
+                .enumerate()
+                .map(|(j, leaf)| parse_word(leaf, &format!("{field}[{j}]")))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let tree = MerkleTree::new(leaves).map_err(|e| format!("Invalid {field}: {e}"))?;
+            store.extend(tree.inner_nodes());
+        } else if let Some(leaves) = entry.get("sparse_merkle_tree") {
+            let field = format!("{field}.sparse_merkle_tree");
+            let leaves = leaves
+                .as_array()
+                .ok_or_else(|| {
+                    format!("Invalid {field}: expected an array of [index, leaf] pairs")
+                })?
+                .iter()
+                .enumerate()
+                .map(|(j, pair)| {
+                    let field = format!("{field}[{j}]");
+                    match pair.as_array().map(Vec::as_slice) {
+                        Some([index, leaf]) => {
+                            let index = parse_felt(index, &format!("{field}.index"))?.as_int();
+                            Ok((index, parse_word(leaf, &format!("{field}.leaf"))?))
+                        }
+                        _ => Err(format!("Invalid {field}: expected an [index, leaf] pair")),
+                    }
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+
+            let tree = SimpleSmt::<SPARSE_MERKLE_TREE_DEPTH>::with_leaves(leaves)
+                .map_err(|e| format!("Invalid {field}: {e}"))?;
+            store.extend(tree.inner_nodes());
+        } else {
+            return Err(format!(
+                "Invalid {field}: expected a `merkle_tree` or `sparse_merkle_tree` entry"
+            ));
+        }
+    }
+
+    Ok(store)
+}
+
+fn parse_advice_inputs(inputs: &Value) -> Result<AdviceInputs, String> {
+    let mut advice_inputs = AdviceInputs::default();
+
+    // The first element of the advice stack is the first one to be popped by `adv_push`
+    if let Some(advice_stack) = inputs.get("advice_stack") {
+        advice_inputs = advice_inputs.with_stack(parse_felt_array(advice_stack, "advice_stack")?);
+    }
+
+    if let Some(advice_map) = inputs.get("advice_map") {
+        let entries = advice_map
+            .as_object()
+            .ok_or_else(|| "Invalid advice_map: expected an object".to_string())?;
+
+        let mut map = Vec::with_capacity(entries.len());
+        for (key, values) in entries {
+            let word = Word::try_from(key.as_str())
+                .map_err(|e| format!("Invalid advice_map key '{key}': {e}"))?;
+            map.push((
+                word,
+                parse_felt_array(values, &format!("advice_map[{key}]"))?,
+            ));
+        }
+        advice_inputs = advice_inputs.with_map(map);
+    }
+
+    if let Some(merkle_store) = inputs.get("merkle_store") {
+        advice_inputs = advice_inputs.with_merkle_store(parse_merkle_store(merkle_store)?);
+    }
+
+    Ok(advice_inputs)
+}
+
+pub fn parse_program_inputs(inputs: &Value) -> Result<(StackInputs, AdviceInputs), String> {
+    Ok((parse_stack_inputs(inputs)?, parse_advice_inputs(inputs)?))
+}
+
+pub fn parse_stack_outputs(stack_outputs: &[String]) -> Result<StackOutputs, String> {
+    let mut outputs = Vec::with_capacity(stack_outputs.len());
+
+    for val_str in stack_outputs {
+        outputs.push(parse_formatted_felt(val_str)?);
+    }
+
+    // Outputs are already ordered top of the stack first
+    StackOutputs::new(outputs).map_err(|e| format!("Failed to create stack outputs: {e}"))
+}
@@ -0,0 +1,567 @@
+use crate::debug_log::DebugEntry;
+use crate::limits::env_or;
+use crate::playground::{ExecutionRequest, PLAYGROUND};
+use crate::progress::{Progress, ProgressEvent, ProgressUpdate};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const WORKERS_ENV: &str = "MASM_PLAYGROUND_WORKERS";
+const QUEUE_CAPACITY_ENV: &str = "MASM_PLAYGROUND_QUEUE_CAPACITY";
+const DEFAULT_QUEUE_CAPACITY: usize = 32;
+
+/// Maximum number of finished jobs kept around for polling; the oldest job is dropped first.
+const MAX_FINISHED_JOBS: usize = 256;
+
+/// The process-wide job queue; its workers are started on first use.
+pub static JOBS: Lazy<JobQueue> = Lazy::new(JobQueue::start);
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Execute,
+    Prove,
+    /// A trace export, queued by `/api/trace`; see [JobQueue::run].
+    #[serde(skip_deserializing)]
+    Trace,
+    /// A test suite, queued by `/api/test`.
+    #[serde(skip_deserializing)]
+    Test,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    fn is_finished(self) -> bool {
+        !matches!(self, JobStatus::Queued | JobStatus::Running)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct JobRequest {
+    pub kind: JobKind,
+    #[serde(flatten)]
+    pub request: ExecutionRequest,
+}
+
+#[derive(Serialize, Clone)]
+pub struct JobInfo {
+    pub id: u64,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// Number of jobs ahead of this one while it is queued.
+    pub queue_position: Option<usize>,
+    pub submitted_at_ms: u64,
+    pub started_at_ms: Option<u64>,
+    pub finished_at_ms: Option<u64>,
+    /// The latest phase the job reported while running.
+    pub progress: Option<ProgressEvent>,
+    /// The `ExecutionResult` or `ProofResult` of a finished job.
+    pub result: Option<Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Clone, Copy)]
+pub struct QueueStatus {
+    pub queued: usize,
+    pub running: usize,
+    pub capacity: usize,
+    pub workers: usize,
+}
+
+/// A message streamed to clients following a job; see [JobQueue::follow].
+#[derive(Serialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobMessage {
+    Submitted {
+        job: JobInfo,
+    },
+    Progress {
+        job_id: u64,
+        #[serde(flatten)]
+        progress: ProgressEvent,
+    },
+    /// The output of a `debug` or `trace` decorator, sent once execution has finished.
+    Debug {
+        job_id: u64,
+        #[serde(flatten)]
+        entry: DebugEntry,
+    },
+    Finished {
+        job: JobInfo,
+    },
+    Error {
+        error: String,
+    },
+}
+
+#[derive(Serialize)]
+pub struct JobResult {
+    pub success: bool,
+    pub job: Option<JobInfo>,
+    pub queue: QueueStatus,
+    pub error: Option<String>,
+}
+
+/// How a task passed to [JobQueue::run] ended, once it was queued.
+pub enum TaskOutcome<T> {
+    Finished(T),
+    /// The job was cancelled while queued or running; a result it produced anyway is discarded.
+    Cancelled,
+    Panicked,
+}
+
+/// Work queued on behalf of a caller that waits for its result; see [JobQueue::run].
+type Task = Box<dyn FnOnce(&Progress) + Send>;
+
+enum Work {
+    Request(Box<JobRequest>),
+    Task(Task),
+}
+
+struct Job {
+    info: JobInfo,
+    /// Taken by the worker that runs the job.
+    work: Option<Work>,
+    /// Set when the job is cancelled, so a running proof stops at its next phase.
+    cancelled: Arc<AtomicBool>,
+    /// Every progress event and debug entry the job reported, in order.
+    events: Vec<ProgressUpdate>,
+}
+
+#[derive(Default)]
+struct JobTable {
+    jobs: BTreeMap<u64, Job>,
+    queue: VecDeque<u64>,
+    running: usize,
+    next_id: u64,
+}
+
+impl JobTable {
+    fn info(&self, id: u64) -> Option<JobInfo> {
+        let mut info = self.jobs.get(&id)?.info.clone();
+        info.queue_position = self.queue.iter().position(|&queued| queued == id);
+        Some(info)
+    }
+
+    fn evict_finished(&mut self) {
+        let finished: Vec<u64> = self
+            .jobs
+            .iter()
+            .filter(|(_, job)| job.info.status.is_finished())
+            .map(|(&id, _)| id)
+            .collect();
+        for id in finished
+            .iter()
+            .take(finished.len().saturating_sub(MAX_FINISHED_JOBS))
+        {
+            self.jobs.remove(id);
+        }
+    }
+}
+
+/// A bounded queue of execution and proving jobs served by a fixed pool of worker threads.
+pub struct JobQueue {
+    table: Mutex<JobTable>,
+    work_available: Condvar,
+    /// Signalled whenever a job reports progress or finishes.
+    job_updated: Condvar,
+    capacity: usize,
+    workers: usize,
+}
+
+impl JobQueue {
+    fn start() -> Self {
+        let default_workers = thread::available_parallelism().map_or(2, |n| n.get());
+        let workers = env_or(WORKERS_ENV, default_workers).max(1);
+        let capacity = env_or(QUEUE_CAPACITY_ENV, DEFAULT_QUEUE_CAPACITY);
+
+        for _ in 0..workers {
+            // Workers block on `JOBS` until this initializer has returned
+            thread::spawn(|| JOBS.work());
+        }
+
+        JobQueue {
+            table: Mutex::new(JobTable::default()),
+            work_available: Condvar::new(),
+            job_updated: Condvar::new(),
+            capacity,
+            workers,
+        }
+    }
+
+    fn result(&self, table: &JobTable, job: Result<Option<JobInfo>, String>) -> JobResult {
+        let queue = QueueStatus {
+            queued: table.queue.len(),
+            running: table.running,
+            capacity: self.capacity,
+            workers: self.workers,
+        };
+        match job {
+            Ok(job) => JobResult {
+                success: true,
+                job,
+                queue,
+                error: None,
+            },
+            Err(e) => JobResult {
+                success: false,
+                job: None,
+                queue,
+                error: Some(e),
+            },
+        }
+    }
+
+    /// Queues a job, failing without side effects if the queue is full.
+    pub fn submit(&self, request: JobRequest) -> JobResult {
+        if !matches!(request.kind, JobKind::Execute | JobKind::Prove) {
+            let table = self.table.lock().unwrap();
+            let error = "Only execute and prove jobs can be submitted".to_string();
+            return self.result(&table, Err(error));
+        }
+        self.enqueue(request.kind, Work::Request(Box::new(request)))
+    }
+
+    /// Runs `task` on a worker and waits for its result, so work that is not a job of its own
+    /// shares the queue and its limits. Returns the rejected submission if the queue is full.
+    pub fn run<T: Send + 'static>(
+        &self,
+        kind: JobKind,
+        task: impl FnOnce(&Progress) -> T + Send + 'static,
+    ) -> Result<TaskOutcome<T>, Box<JobResult>> {
+        let (sender, receiver) = mpsc::channel();
+        let task: Task = Box::new(move |progress| {
+            let _ = sender.send(task(progress));
+        });
+        let submitted = self.enqueue(kind, Work::Task(task));
+        let Some(id) = submitted.job.as_ref().map(|job| job.id) else {
+            return Err(Box::new(submitted));
+        };
+
+        // The sender is dropped without sending if the task panics or is cancelled while queued
+        let value = receiver.recv().ok();
+        let table = self.table.lock().unwrap();
+        let status = table.jobs.get(&id).map(|job| job.info.status);
+        Ok(match value {
+            _ if status == Some(JobStatus::Cancelled) => TaskOutcome::Cancelled,
+            Some(value) => TaskOutcome::Finished(value),
+            None => TaskOutcome::Panicked,
+        })
+    }
+
+    fn enqueue(&self, kind: JobKind, work: Work) -> JobResult {
+        let mut table = self.table.lock().unwrap();
+        if table.queue.len() >= self.capacity {
+            let error = format!("Job queue is full ({} jobs waiting)", self.capacity);
+            return self.result(&table, Err(error));
+        }
+
+        table.next_id += 1;
+        let id = table.next_id;
+        let info = JobInfo {
+            id,
+            kind,
+            status: JobStatus::Queued,
+            queue_position: None,
+            submitted_at_ms: now_ms(),
+            started_at_ms: None,
+            finished_at_ms: None,
+            progress: None,
+            result: None,
+            error: None,
+        };
+        table.jobs.insert(
+            id,
+            Job {
+                info,
+                work: Some(work),
+                cancelled: Arc::new(AtomicBool::new(false)),
+                events: Vec::new(),
+            },
+        );
+        table.queue.push_back(id);
+        self.work_available.notify_one();
+
+        let info = table.info(id);
+        self.result(&table, Ok(info))
+    }
+
+    pub fn status(&self, id: u64) -> JobResult {
+        let table = self.table.lock().unwrap();
+        let info = table.info(id).ok_or_else(|| format!("Unknown job {id}"));
+        self.result(&table, info.map(Some))
+    }
+
+    pub fn queue_status(&self) -> JobResult {
+        let table = self.table.lock().unwrap();
+        self.result(&table, Ok(None))
+    }
+
+    /// Cancels a job. A queued job is removed from the queue; a running one stops at the next MAST
+    /// node it executes or the next phase of the prover. Either way the result of a cancelled job
+    /// is discarded.
+    pub fn cancel(&self, id: u64) -> JobResult {
+        let mut table = self.table.lock().unwrap();
+        let Some(job) = table.jobs.get_mut(&id) else {
+            return self.result(&table, Err(format!("Unknown job {id}")));
+        };
+        if job.info.status.is_finished() {
+            let error = format!("Job {id} has already finished");
+            return self.result(&table, Err(error));
+        }
+
+        job.info.status = JobStatus::Cancelled;
+        job.info.finished_at_ms = Some(now_ms());
+        job.work = None;
+        job.cancelled.store(true, Ordering::Relaxed);
+        table.queue.retain(|&queued| queued != id);
+        self.job_updated.notify_all();
+
+        let info = table.info(id);
+        self.result(&table, Ok(info))
+    }
+
+    /// Blocks until the job has finished, returning `None` if it is unknown or was evicted.
+    pub fn wait(&self, id: u64) -> Option<JobInfo> {
+        let mut table = self.table.lock().unwrap();
+        loop {
+            let info = table.info(id)?;
+            if info.status.is_finished() {
+                return Some(info);
+            }
+            table = self.job_updated.wait(table).unwrap();
+        }
+    }
+
+    /// Passes each progress event of a job to `send` as it happens, followed by the finished job.
+    /// Returns early once `send` returns false, e.g. because the client has gone away.
+    pub fn follow(&self, id: u64, mut send: impl FnMut(JobMessage) -> bool) {
+        let mut seen = 0;
+        let mut table = self.table.lock().unwrap();
+        loop {
+            let Some(job) = table.jobs.get(&id) else {
+                send(JobMessage::Error {
+                    error: format!("Unknown job {id}"),
+                });
+                return;
+            };
+            let events = job.events[seen..].to_vec();
+            let finished = job.info.status.is_finished();
+            if events.is_empty() && !finished {
+                table = self.job_updated.wait(table).unwrap();
+                continue;
+            }
+            seen += events.len();
+            let info = table.info(id);
+            drop(table);
+
+            for event in events {
+                let message = match event {
+                    ProgressUpdate::Phase(progress) => JobMessage::Progress {
+                        job_id: id,
+                        progress,
+                    },
+                    ProgressUpdate::Debug(entry) => JobMessage::Debug { job_id: id, entry },
+                };
+                if !send(message) {
+                    return;
+                }
+            }
+            if finished {
+                if let Some(job) = info {
+                    send(JobMessage::Finished { job });
+                }
+                return;
+            }
+            table = self.table.lock().unwrap();
+        }
+    }
+
+    fn record_progress(&self, id: u64, event: ProgressUpdate) {
+        let mut table = self.table.lock().unwrap();
+        if let Some(job) = table.jobs.get_mut(&id) {
+            if job.info.status == JobStatus::Running {
+                if let ProgressUpdate::Phase(progress) = &event {
+                    job.info.progress = Some(progress.clone());
+                }
+                job.events.push(event);
+                self.job_updated.notify_all();
+            }
+        }
+    }
+
+    fn work(&self) {
+        loop {
+            let (id, work, progress) = {
+                let mut table = self.table.lock().unwrap();
+                let id = loop {
+                    if let Some(id) = table.queue.pop_front() {
+                        break id;
+                    }
+                    table = self.work_available.wait(table).unwrap();
+                };
+                table.running += 1;
+                let job = table.jobs.get_mut(&id).unwrap();
+                job.info.status = JobStatus::Running;
+                job.info.started_at_ms = Some(now_ms());
+                let progress = Progress::new(job.cancelled.clone(), move |event| {
+                    JOBS.record_progress(id, event)
+                });
+                (id, job.work.take().unwrap(), progress)
+            };
+
+            // A panicking job must not take its worker down with it
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| match work {
+                Work::Request(request) => run_job(&request, &progress),
+                Work::Task(task) => {
+                    task(&progress);
+                    (true, Value::Null)
+                }
+            }));
+
+            let mut table = self.table.lock().unwrap();
+            table.running -= 1;
+            if let Some(job) = table.jobs.get_mut(&id) {
+                if job.info.status == JobStatus::Running {
+                    job.info.finished_at_ms = Some(now_ms());
+                    match outcome {
+                        Ok((success, result)) => {
+                            job.info.status = if success {
+                                JobStatus::Done
+                            } else {
+                                JobStatus::Failed
+                            };
+                            job.info.result = Some(result);
+                        }
+                        Err(_) => {
+                            job.info.status = JobStatus::Failed;
+                            job.info.error = Some(format!("Job {id} panicked"));
+                        }
+                    }
+                }
+            }
+            table.evict_finished();
+            self.job_updated.notify_all();
+        }
+    }
+}
+
+/// Runs a job, returning whether it succeeded along with its serialized result.
+fn run_job(job: &JobRequest, progress: &Progress) -> (bool, Value) {
+    match job.kind {
+        JobKind::Execute => {
+            let result = PLAYGROUND.execute_with_progress(&job.request, progress);
+            (result.success, serde_json::to_value(result).unwrap())
+        }
+        JobKind::Prove => {
+            let result = PLAYGROUND.prove_with_progress(&job.request, progress);
+            (result.success, serde_json::to_value(result).unwrap())
+        }
+        JobKind::Trace | JobKind::Test => unreachable!("rejected by JobQueue::submit"),
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// A queue of its own, so tests neither depend on nor disturb `JOBS`. Nothing runs until
+    /// workers are started with [JobQueue::work].
+    fn queue(capacity: usize) -> &'static JobQueue {
+        Box::leak(Box::new(JobQueue {
+            table: Mutex::new(JobTable::default()),
+            work_available: Condvar::new(),
+            job_updated: Condvar::new(),
+            capacity,
+            workers: 0,
+        }))
+    }
+
+    fn enqueue(queue: &JobQueue) -> JobResult {
+        queue.enqueue(JobKind::Execute, Work::Task(Box::new(|_| {})))
+    }
+
+    #[test]
+    fn full_queues_reject_jobs_without_side_effects() {
+        let queue = queue(2);
+        let positions: Vec<_> = (0..2)
+            .map(|_| enqueue(queue).job.unwrap().queue_position)
+            .collect();
+        assert_eq!(positions, [Some(0), Some(1)]);
+
+        let rejected = enqueue(queue);
+        assert!(!rejected.success);
+        assert!(rejected.job.is_none());
+        assert_eq!(
+            rejected.error.as_deref(),
+            Some("Job queue is full (2 jobs waiting)")
+        );
+        assert_eq!(rejected.queue.queued, 2);
+        assert!(!queue.status(3).success);
+    }
+
+    #[test]
+    fn cancelled_jobs_leave_the_queue() {
+        let queue = queue(2);
+        enqueue(queue);
+        enqueue(queue);
+
+        let cancelled = queue.cancel(1).job.unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+        assert_eq!(cancelled.queue_position, None);
+        assert!(cancelled.finished_at_ms.is_some());
+        assert_eq!(queue.status(2).job.unwrap().queue_position, Some(0));
+        assert_eq!(
+            queue.cancel(1).error.as_deref(),
+            Some("Job 1 has already finished")
+        );
+        assert_eq!(queue.cancel(99).error.as_deref(), Some("Unknown job 99"));
+
+        // The cancelled job's slot is free again
+        assert!(enqueue(queue).success);
+    }
+
+    #[test]
+    fn running_tasks_are_cancelled() {
+        let queue = queue(1);
+        thread::spawn(|| queue.work());
+        let runner = thread::spawn(|| {
+            queue.run(JobKind::Test, |progress| {
+                while !progress.is_cancelled() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            })
+        });
+
+        while queue.status(1).job.map(|job| job.status) != Some(JobStatus::Running) {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(queue.cancel(1).success);
+        let outcome = runner.join().unwrap();
+        assert!(matches!(outcome, Ok(TaskOutcome::Cancelled)));
+        assert_eq!(queue.wait(1).unwrap().status, JobStatus::Cancelled);
+    }
+}
@@ -1,21 +1,75 @@
+pub mod artifacts;
 pub mod client;
+pub mod compile;
+pub mod debug_log;
+pub mod debugger;
+pub mod diagnostics;
+pub mod disassembler;
+pub mod examples;
+pub mod host;
+pub mod inputs;
+pub mod jobs;
+pub mod limits;
+pub mod mast_tree;
+pub mod memory;
+pub mod outputs;
+pub mod playground;
+pub mod profiler;
+pub mod progress;
+pub mod proving;
+pub mod storage;
+pub mod test_runner;
+pub mod trace_export;
+pub mod trace_summary;
 
 #[cfg(feature = "web_server")]
 pub mod web_server;
 
 use client::*;
+use debugger::*;
+use storage::*;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Check the examples in the background so a broken catalog does not delay the window
+    std::thread::spawn(|| {
+        for problem in playground::PLAYGROUND.validate_examples() {
+            eprintln!("⚠️  {problem}");
+        }
+    });
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            storage::set_data_dir(app.path().app_data_dir()?);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             instantiate,
             exec_program,
             exec_program_with_inputs,
             generate_proof_with_inputs,
-            get_example_programs
+            verify_proof,
+            start_debug_session,
+            debug_command,
+            end_debug_session,
+            save_program,
+            list_programs,
+            load_program,
+            rename_program,
+            delete_program,
+            get_example_programs,
+            get_example,
+            run_tests,
+            start_job,
+            cancel_job,
+            export_program,
+            get_mast_tree,
+            disassemble_program,
+            export_trace,
+            compile_program
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,181 @@
+use crate::progress::Progress;
+use miden_processor::ExecutionOptions;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+const MAX_CYCLES_ENV: &str = "MASM_PLAYGROUND_MAX_CYCLES";
+const EXPECTED_CYCLES_ENV: &str = "MASM_PLAYGROUND_EXPECTED_CYCLES";
+const TIMEOUT_MS_ENV: &str = "MASM_PLAYGROUND_TIMEOUT_MS";
+const MAX_BODY_BYTES_ENV: &str = "MASM_PLAYGROUND_MAX_BODY_BYTES";
+
+/// Server-wide ceilings, read once from the environment.
+pub static LIMITS: Lazy<ResourceLimits> = Lazy::new(ResourceLimits::from_env);
+
+/// The most a single request is allowed to consume.
+#[derive(Serialize, Clone)]
+pub struct ResourceLimits {
+    pub max_cycles: u32,
+    pub expected_cycles: u32,
+    pub timeout_ms: u64,
+    pub max_body_bytes: usize,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        ResourceLimits {
+            max_cycles: 1 << 24,
+            expected_cycles: 64,
+            timeout_ms: 30_000,
+            max_body_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+/// Per-request limits; anything above the server ceiling is capped to it.
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct LimitOverrides {
+    pub max_cycles: Option<u32>,
+    pub expected_cycles: Option<u32>,
+    pub timeout_ms: Option<u64>,
+}
+
+/// The limits that apply to one request after overrides have been capped.
+#[derive(Clone, Copy)]
+pub struct EffectiveLimits {
+    pub max_cycles: u32,
+    pub expected_cycles: u32,
+    pub timeout: Duration,
+}
+
+impl ResourceLimits {
+    fn from_env() -> Self {
+        let defaults = ResourceLimits::default();
+        ResourceLimits {
+            max_cycles: env_or(MAX_CYCLES_ENV, defaults.max_cycles),
+            expected_cycles: env_or(EXPECTED_CYCLES_ENV, defaults.expected_cycles),
+            timeout_ms: env_or(TIMEOUT_MS_ENV, defaults.timeout_ms),
+            max_body_bytes: env_or(MAX_BODY_BYTES_ENV, defaults.max_body_bytes),
+        }
+    }
+
+    pub fn resolve(&self, overrides: &LimitOverrides) -> EffectiveLimits {
+        let max_cycles = overrides
+            .max_cycles
+            .map_or(self.max_cycles, |cycles| cycles.min(self.max_cycles));
+        let expected_cycles = overrides
+            .expected_cycles
+            .unwrap_or(self.expected_cycles)
+            .min(max_cycles);
+        let timeout_ms = overrides
+            .timeout_ms
+            .map_or(self.timeout_ms, |ms| ms.min(self.timeout_ms));
+
+        EffectiveLimits {
+            max_cycles,
+            expected_cycles,
+            timeout: Duration::from_millis(timeout_ms),
+        }
+    }
+}
+
+impl EffectiveLimits {
+    /// Tracing is enabled so that [interruptible](crate::progress::interruptible) programs check
+    /// for cancellation.
+    pub fn execution_options(&self) -> Result<ExecutionOptions, String> {
+        ExecutionOptions::new(Some(self.max_cycles), self.expected_cycles, true, false)
+            .map_err(|e| format!("Invalid execution limits: {e}"))
+    }
+}
+
+pub(crate) fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Runs `task`, cancelling `progress` once `timeout` has passed, and returns `None` if it did.
+///
+/// Cancelling only stops work that checks `progress`: the prover under [Progress::run], and
+/// execution of an [interruptible](crate::progress::interruptible) program by a host that
+/// [watches it](crate::host::PlaygroundHost::with_progress).
+pub fn run_with_timeout<T>(
+    timeout: Duration,
+    progress: &Progress,
+    task: impl FnOnce() -> T,
+) -> Option<T> {
+    let (finished, done) = mpsc::channel::<()>();
+    // `finished` moves into the scope so a panicking task releases the watchdog
+    thread::scope(move |scope| {
+        let watchdog = scope.spawn(move || {
+            let timed_out = done.recv_timeout(timeout) == Err(RecvTimeoutError::Timeout);
+            if timed_out {
+                progress.cancel();
+            }
+            timed_out
+        });
+        let value = task();
+        // The watchdog is gone if it already timed out
+        let _ = finished.send(());
+        let timed_out = watchdog.join().expect("the watchdog does not panic");
+        (!timed_out).then_some(value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: ResourceLimits = ResourceLimits {
+        max_cycles: 1 << 20,
+        expected_cycles: 64,
+        timeout_ms: 10_000,
+        max_body_bytes: 1024,
+    };
+
+    #[test]
+    fn defaults_to_the_server_limits() {
+        let limits = LIMITS.resolve(&LimitOverrides::default());
+        assert_eq!(limits.max_cycles, 1 << 20);
+        assert_eq!(limits.expected_cycles, 64);
+        assert_eq!(limits.timeout, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn accepts_lower_overrides() {
+        let limits = LIMITS.resolve(&LimitOverrides {
+            max_cycles: Some(4096),
+            expected_cycles: Some(128),
+            timeout_ms: Some(500),
+        });
+        assert_eq!(limits.max_cycles, 4096);
+        assert_eq!(limits.expected_cycles, 128);
+        assert_eq!(limits.timeout, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn caps_overrides_to_the_server_limits() {
+        let limits = LIMITS.resolve(&LimitOverrides {
+            max_cycles: Some(u32::MAX),
+            expected_cycles: Some(u32::MAX),
+            timeout_ms: Some(u64::MAX),
+        });
+        assert_eq!(limits.max_cycles, 1 << 20);
+        assert_eq!(limits.expected_cycles, 1 << 20);
+        assert_eq!(limits.timeout, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn caps_expected_cycles_to_max_cycles() {
+        let limits = LIMITS.resolve(&LimitOverrides {
+            max_cycles: Some(32),
+            ..LimitOverrides::default()
+        });
+        assert_eq!(limits.max_cycles, 32);
+        assert_eq!(limits.expected_cycles, 32);
+    }
+}
@@ -0,0 +1,268 @@
+use crate::diagnostics::Diagnostic;
+use crate::playground::{resolve_source_location, ErrorKind, ModuleSource, SourceLocation};
+use crate::profiler::{block_kind, procedure_name};
+use miden_core::{
+    mast::{MastForest, MastNode, MastNodeId},
+    Decorator,
+};
+use miden_vm::{
+    assembly::{debuginfo::Location, DefaultSourceManager},
+    Program, Word,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Deserialize)]
+pub struct MastTreeRequest {
+    #[serde(default)]
+    pub program: String,
+    /// A precompiled program, as returned by `/api/export`, shown instead of `program`.
+    pub mast: Option<Vec<u8>>,
+    #[serde(default)]
+    pub modules: Vec<ModuleSource>,
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum DecoratorPosition {
+    BeforeEnter,
+    AfterExit,
+    /// Inside a basic block, before the operation at `op_index`.
+    Operation,
+}
+
+#[derive(Serialize)]
+pub struct MastDecorator {
+    pub position: DecoratorPosition,
+    pub op_index: Option<usize>,
+    /// `asm_op`, `debug` or `trace`.
+    pub kind: String,
+    pub description: String,
+    pub location: Option<SourceLocation>,
+}
+
+#[derive(Serialize)]
+pub struct MastTreeNode {
+    /// Index of the node in the MAST forest; nodes that occur more than once share it.
+    pub id: u32,
+    pub kind: String,
+    pub digest: String,
+    /// Name of the procedure rooted at this node, if it is a procedure root.
+    pub procedure: Option<String>,
+    /// Operations of a basic block, in execution order.
+    pub operations: Vec<String>,
+    pub decorators: Vec<MastDecorator>,
+    /// Where the first assembly instruction of the node was written. Left out for repeated nodes.
+    pub location: Option<SourceLocation>,
+    /// Number of parent nodes referencing this node; above one for shared subtrees.
+    pub references: u32,
+    /// Whether this node was already expanded earlier in the tree, in which case its operations,
+    /// decorators and children are left out here.
+    pub repeated: bool,
+    pub children: Vec<MastTreeNode>,
+}
+
+#[derive(Serialize)]
+pub struct MastTreeResult {
+    pub success: bool,
+    pub program_hash: Option<String>,
+    pub root: Option<MastTreeNode>,
+    /// Number of nodes in the MAST forest, including those not reachable from the entrypoint.
+    pub forest_nodes: Option<usize>,
+    /// Number of distinct nodes reachable from the entrypoint.
+    pub unique_nodes: Option<usize>,
+    /// Number of reachable nodes referenced from more than one parent.
+    pub shared_nodes: Option<usize>,
+    pub error: Option<String>,
+    pub error_kind: Option<ErrorKind>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl MastTreeResult {
+    pub fn failure(error_kind: ErrorKind, error: String, diagnostics: Vec<Diagnostic>) -> Self {
+        MastTreeResult {
+            success: false,
+            program_hash: None,
+            root: None,
+            forest_nodes: None,
+            unique_nodes: None,
+            shared_nodes: None,
+            error: Some(error),
+            error_kind: Some(error_kind),
+            diagnostics,
+        }
+    }
+}
+
+pub(crate) fn children(node: &MastNode) -> Vec<MastNodeId> {
+    let mut children = Vec::new();
+    node.append_children_to(&mut children);
+    children
+}
+
+/// Counts how many parent nodes reference each node reachable from `root`.
+fn count_references(forest: &MastForest, root: MastNodeId) -> HashMap<MastNodeId, u32> {
+    let mut references = HashMap::from([(root, 0)]);
+    let mut pending = vec![root];
+    while let Some(node_id) = pending.pop() {
+        for child in children(&forest[node_id]) {
+            let count = references.entry(child).or_insert(0);
+            if *count == 0 {
+                pending.push(child);
+            }
+            *count += 1;
+        }
+    }
+    references
+}
+
+/// Names the procedure rooted at `node_id`, preferring library names over assembly contexts.
+pub(crate) fn procedure_root_name(
+    forest: &MastForest,
+    node_id: MastNodeId,
+    entrypoint: MastNodeId,
+    library_procedures: &HashMap<Word, String>,
+) -> Option<String> {
+    if !forest.is_procedure_root(node_id) {
+        return None;
+    }
+    Some(match library_procedures.get(&forest[node_id].digest()) {
+        Some(name) => name.clone(),
+        None => procedure_name(forest, node_id, node_id == entrypoint),
+    })
+}
+
+struct TreeBuilder<'a> {
+    forest: &'a MastForest,
+    entrypoint: MastNodeId,
+    source_manager: &'a DefaultSourceManager,
+    library_procedures: &'a HashMap<Word, String>,
+    references: HashMap<MastNodeId, u32>,
+    expanded: HashSet<MastNodeId>,
+}
+
+impl TreeBuilder<'_> {
+    fn location(&self, location: Option<&Location>) -> Option<SourceLocation> {
+        resolve_source_location(self.source_manager, location?)
+    }
+
+    fn decorator(
+        &self,
+        position: DecoratorPosition,
+        op_index: Option<usize>,
+        decorator: &Decorator,
+    ) -> MastDecorator {
+        let (kind, location) = match decorator {
+            Decorator::AsmOp(asmop) => ("asm_op", self.location(asmop.location())),
+            Decorator::Debug(_) => ("debug", None),
+            Decorator::Trace(_) => ("trace", None),
+        };
+        MastDecorator {
+            position,
+            op_index,
+            kind: kind.to_string(),
+            description: decorator.to_string(),
+            location,
+        }
+    }
+
+    fn build(&mut self, node_id: MastNodeId) -> MastTreeNode {
+        let node = &self.forest[node_id];
+        let mut tree_node = MastTreeNode {
+            id: node_id.as_u32(),
+            kind: block_kind(node).to_string(),
+            digest: node.digest().to_string(),
+            procedure: procedure_root_name(
+                self.forest,
+                node_id,
+                self.entrypoint,
+                self.library_procedures,
+            ),
+            operations: Vec::new(),
+            decorators: Vec::new(),
+            location: None,
+            references: self.references.get(&node_id).copied().unwrap_or(0),
+            repeated: !self.expanded.insert(node_id),
+            children: Vec::new(),
+        };
+        if tree_node.repeated {
+            return tree_node;
+        }
+
+        let before_enter = node
+            .before_enter()
+            .iter()
+            .map(|&id| (DecoratorPosition::BeforeEnter, None, id));
+        let in_block = node.get_basic_block().into_iter().flat_map(|block| {
+            block
+                .decorators()
+                .iter()
+                .map(|&(index, id)| (DecoratorPosition::Operation, Some(index), id))
+        });
+        let after_exit = node
+            .after_exit()
+            .iter()
+            .map(|&id| (DecoratorPosition::AfterExit, None, id));
+        for (position, op_index, id) in before_enter.chain(in_block).chain(after_exit) {
+            let decorator = self.decorator(position, op_index, &self.forest[id]);
+            tree_node.decorators.push(decorator);
+        }
+
+        if let Some(block) = node.get_basic_block() {
+            tree_node.operations = block.operations().map(|op| op.to_string()).collect();
+        }
+        tree_node.children = children(node)
+            .into_iter()
+            .map(|child| self.build(child))
+            .collect();
+
+        // Control nodes rarely carry assembly decorators, so they start where their body starts
+        tree_node.location = tree_node
+            .decorators
+            .iter()
+            .find_map(|decorator| decorator.location.clone())
+            .or_else(|| {
+                tree_node
+                    .children
+                    .iter()
+                    .find_map(|child| child.location.clone())
+            });
+        tree_node
+    }
+}
+
+/// Walks the MAST of `program` from its entrypoint. Every node is expanded the first time it is
+/// reached; later occurrences of a shared subtree are marked as `repeated`.
+pub fn mast_tree(
+    program: &Program,
+    source_manager: &DefaultSourceManager,
+    library_procedures: &HashMap<Word, String>,
+) -> MastTreeResult {
+    let forest = program.mast_forest();
+    let entrypoint = program.entrypoint();
+    let references = count_references(forest, entrypoint);
+    let unique_nodes = references.len();
+    let shared_nodes = references.values().filter(|&&count| count > 1).count();
+
+    let mut builder = TreeBuilder {
+        forest,
+        entrypoint,
+        source_manager,
+        library_procedures,
+        references,
+        expanded: HashSet::new(),
+    };
+    let root = builder.build(entrypoint);
+
+    MastTreeResult {
+        success: true,
+        program_hash: Some(program.hash().to_string()),
+        root: Some(root),
+        forest_nodes: Some(forest.num_nodes() as usize),
+        unique_nodes: Some(unique_nodes),
+        shared_nodes: Some(shared_nodes),
+        error: None,
+        error_kind: None,
+        diagnostics: Vec::new(),
+    }
+}
@@ -0,0 +1,173 @@
+use miden_air::trace::chiplets::{
+    memory::{MEMORY_ACCESS_WORD, MEMORY_READ},
+    MEMORY_CLK_COL_IDX, MEMORY_CTX_COL_IDX, MEMORY_IDX0_COL_IDX, MEMORY_IDX1_COL_IDX,
+    MEMORY_IS_READ_COL_IDX, MEMORY_IS_WORD_ACCESS_COL_IDX, MEMORY_V_COL_RANGE, MEMORY_WORD_COL_IDX,
+};
+use miden_air::trace::CHIPLETS_OFFSET;
+use miden_core::{Felt, ONE, ZERO};
+use miden_vm::ExecutionTrace;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use winter_prover::Trace;
+
+/// Number of cells or accesses returned per page when the request does not say.
+const DEFAULT_PAGE_SIZE: usize = 1024;
+const MAX_PAGE_SIZE: usize = 65_536;
+
+/// Which part of memory to report. Address and context filters apply to both the final memory
+/// and the access log; `offset` and `limit` page through each of them separately.
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct MemoryQuery {
+    pub context: Option<u32>,
+    /// First element address to include.
+    pub start_address: Option<u32>,
+    /// Element address to stop before.
+    pub end_address: Option<u32>,
+    /// First clock cycle to include in the access log.
+    pub start_cycle: Option<u32>,
+    /// Clock cycle to stop the access log before.
+    pub end_cycle: Option<u32>,
+    pub offset: usize,
+    pub limit: Option<usize>,
+    /// Returns everything that matches rather than a page of it. Only set internally, e.g. by the
+    /// test runner, which needs every cell it compares against.
+    #[serde(skip)]
+    pub(crate) unpaged: bool,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryAccessKind {
+    Read,
+    Write,
+}
+
+/// The value of one memory element when the program finished.
+#[derive(Serialize, Clone)]
+pub struct MemoryCell {
+    pub context: u32,
+    pub address: u32,
+    pub value: String,
+}
+
+#[derive(Serialize, Clone)]
+pub struct MemoryAccess {
+    pub clk: u32,
+    pub context: u32,
+    pub kind: MemoryAccessKind,
+    /// Whether a whole word was accessed rather than a single element.
+    pub word: bool,
+    /// Address of the element, or of the first element of the word.
+    pub address: u32,
+    /// The value read or written, one element per address starting at `address`.
+    pub values: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct MemoryReport {
+    /// Every element written during execution, ordered by context and address.
+    pub cells: Vec<MemoryCell>,
+    pub total_cells: usize,
+    /// Reads and writes in the order they happened.
+    pub accesses: Vec<MemoryAccess>,
+    pub total_accesses: usize,
+}
+
+/// Reads memory accesses back from the rows of the memory chiplet in the execution trace.
+fn trace_accesses(trace: &ExecutionTrace) -> Vec<MemoryAccess> {
+    let main_trace = trace.main_segment();
+    let column = |index: usize, row: usize| main_trace.get(index, row);
+
+    let mut accesses = Vec::new();
+    for row in 0..main_trace.num_rows() {
+        let is_memory_row = column(CHIPLETS_OFFSET, row) == ONE
+            && column(CHIPLETS_OFFSET + 1, row) == ONE
+            && column(CHIPLETS_OFFSET + 2, row) == ZERO;
+        if !is_memory_row {
+            continue;
+        }
+
+        let word_address = column(MEMORY_WORD_COL_IDX, row).as_int() as u32;
+        let word: Vec<Felt> = MEMORY_V_COL_RANGE.map(|index| column(index, row)).collect();
+        let is_word_access = column(MEMORY_IS_WORD_ACCESS_COL_IDX, row) == MEMORY_ACCESS_WORD;
+        let (address, values) = if is_word_access {
+            (word_address, word)
+        } else {
+            let index = 2 * column(MEMORY_IDX1_COL_IDX, row).as_int()
+                + column(MEMORY_IDX0_COL_IDX, row).as_int();
+            (word_address + index as u32, vec![word[index as usize]])
+        };
+
+        accesses.push(MemoryAccess {
+            clk: column(MEMORY_CLK_COL_IDX, row).as_int() as u32,
+            context: column(MEMORY_CTX_COL_IDX, row).as_int() as u32,
+            kind: if column(MEMORY_IS_READ_COL_IDX, row) == MEMORY_READ {
+                MemoryAccessKind::Read
+            } else {
+                MemoryAccessKind::Write
+            },
+            word: is_word_access,
+            address,
+            values: values.iter().map(|value| value.to_string()).collect(),
+        });
+    }
+
+    // The chiplet orders accesses by context and address, so restore execution order
+    accesses.sort_by_key(|access| access.clk);
+    accesses
+}
+
+pub fn memory_report(trace: &ExecutionTrace, query: &MemoryQuery) -> MemoryReport {
+    let accesses = trace_accesses(trace);
+
+    let mut final_memory = BTreeMap::new();
+    for access in accesses
+        .iter()
+        .filter(|a| a.kind == MemoryAccessKind::Write)
+    {
+        for (address, value) in (access.address..).zip(&access.values) {
+            final_memory.insert((access.context, address), value.clone());
+        }
+    }
+
+    let in_range = |context: u32, address: u32, len: usize| {
+        query.context.is_none_or(|ctx| ctx == context)
+            && query
+                .start_address
+                .is_none_or(|start| address + len as u32 > start)
+            && query.end_address.is_none_or(|end| address < end)
+    };
+    let limit = if query.unpaged {
+        usize::MAX
+    } else {
+        query.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)
+    };
+
+    let cells: Vec<MemoryCell> = final_memory
+        .into_iter()
+        .filter(|&((context, address), _)| in_range(context, address, 1))
+        .map(|((context, address), value)| MemoryCell {
+            context,
+            address,
+            value,
+        })
+        .collect();
+    let accesses: Vec<MemoryAccess> = accesses
+        .into_iter()
+        .filter(|a| in_range(a.context, a.address, a.values.len()))
+        .filter(|a| query.start_cycle.is_none_or(|start| a.clk >= start))
+        .filter(|a| query.end_cycle.is_none_or(|end| a.clk < end))
+        .collect();
+
+    MemoryReport {
+        total_cells: cells.len(),
+        cells: cells.into_iter().skip(query.offset).take(limit).collect(),
+        total_accesses: accesses.len(),
+        accesses: accesses
+            .into_iter()
+            .skip(query.offset)
+            .take(limit)
+            .collect(),
+    }
+}
@@ -0,0 +1,182 @@
+use crate::host::PlaygroundHost;
+use crate::limits::run_with_timeout;
+use crate::progress::{interruptible, Progress};
+use miden_core::{Felt, StarkField};
+use miden_vm::{assembly::DefaultSourceManager, execute_iter, AdviceInputs, Program, StackInputs};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How stack elements are rendered in results.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    #[default]
+    Decimal,
+    Hex,
+    /// Elements above half the field modulus are shown as negative numbers.
+    Signed,
+    /// High and low 32-bit limbs, separated by a colon.
+    U32Limbs,
+}
+
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct OutputSettings {
+    pub format: OutputFormat,
+    /// Maximum number of stack elements to return, counting from the top.
+    pub depth: Option<usize>,
+}
+
+/// The output stack rendered according to [OutputSettings].
+pub struct FormattedStack {
+    pub elements: Vec<String>,
+    /// Whether `depth` cut off elements further down the stack.
+    pub truncated: bool,
+}
+
+pub fn format_felt(value: Felt, format: OutputFormat) -> String {
+    let value = value.as_int();
+    match format {
+        OutputFormat::Decimal => value.to_string(),
+        OutputFormat::Hex => format!("{value:#x}"),
+        OutputFormat::Signed if value > Felt::MODULUS / 2 => {
+            format!("-{}", Felt::MODULUS - value)
+        }
+        OutputFormat::Signed => value.to_string(),
+        OutputFormat::U32Limbs => format!("{}:{}", value >> 32, value as u32),
+    }
+}
+
+/// Parses an element in any [OutputFormat], so formatted outputs can be passed back for
+/// verification.
+pub fn parse_formatted_felt(value: &str) -> Result<Felt, String> {
+    let invalid = |e: &dyn std::fmt::Display| format!("Invalid number '{value}': {e}");
+    let int = if let Some((high, low)) = value.split_once(':') {
+        let high: u32 = high.parse().map_err(|e| invalid(&e))?;
+        let low: u32 = low.parse().map_err(|e| invalid(&e))?;
+        ((high as u64) << 32) | low as u64
+    } else if let Some(hex) = value.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16).map_err(|e| invalid(&e))?
+    } else if let Some(negated) = value.strip_prefix('-') {
+        let negated: u64 = negated.parse().map_err(|e| invalid(&e))?;
+        if negated == 0 || negated > Felt::MODULUS / 2 {
+            return Err(invalid(&"out of range for a signed element"));
+        }
+        Felt::MODULUS - negated
+    } else {
+        value.parse().map_err(|e| invalid(&e))?
+    };
+    Felt::try_from(int).map_err(|e| invalid(&e))
+}
+
+pub fn format_stack(stack: &[Felt], settings: &OutputSettings) -> FormattedStack {
+    let depth = settings.depth.unwrap_or(stack.len()).min(stack.len());
+    FormattedStack {
+        elements: stack[..depth]
+            .iter()
+            .map(|&value| format_felt(value, settings.format))
+            .collect(),
+        truncated: depth < stack.len(),
+    }
+}
+
+/// Replays a program to recover its final stack, including the elements in the overflow table.
+///
+/// Only used when execution failed because more than 16 elements were left on the stack, since
+/// the VM reports nothing but their number. Returns `None` if the replay is still running after
+/// `timeout` or `progress` is cancelled.
+pub fn final_stack(
+    program: &Program,
+    stack_inputs: StackInputs,
+    advice_inputs: AdviceInputs,
+    host: PlaygroundHost,
+    source_manager: Arc<DefaultSourceManager>,
+    progress: &Progress,
+    timeout: Duration,
+) -> Option<Vec<Felt>> {
+    let mut host = host.with_progress(progress);
+    let states = run_with_timeout(timeout, progress, || {
+        execute_iter(
+            &interruptible(program),
+            stack_inputs,
+            advice_inputs,
+            &mut host,
+            source_manager,
+        )
+    })?;
+    if progress.is_cancelled() {
+        return None;
+    }
+    states.map_while(Result::ok).last().map(|state| state.stack)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatted_elements_parse_back() {
+        let values = [
+            0,
+            1,
+            42,
+            u32::MAX as u64,
+            Felt::MODULUS / 2,
+            Felt::MODULUS - 1,
+        ];
+        let formats = [
+            OutputFormat::Decimal,
+            OutputFormat::Hex,
+            OutputFormat::Signed,
+            OutputFormat::U32Limbs,
+        ];
+        for value in values {
+            for format in formats {
+                let formatted = format_felt(Felt::new(value), format);
+                assert_eq!(
+                    parse_formatted_felt(&formatted),
+                    Ok(Felt::new(value)),
+                    "{formatted} ({format:?})"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn formats_elements() {
+        let minus_one = Felt::new(Felt::MODULUS - 1);
+        assert_eq!(
+            format_felt(minus_one, OutputFormat::Decimal),
+            "18446744069414584320"
+        );
+        assert_eq!(
+            format_felt(minus_one, OutputFormat::Hex),
+            "0xffffffff00000000"
+        );
+        assert_eq!(format_felt(minus_one, OutputFormat::Signed), "-1");
+        assert_eq!(
+            format_felt(minus_one, OutputFormat::U32Limbs),
+            "4294967295:0"
+        );
+        assert_eq!(format_felt(Felt::new(7), OutputFormat::Signed), "7");
+    }
+
+    #[test]
+    fn rejects_out_of_range_elements() {
+        let modulus = Felt::MODULUS;
+        for value in [
+            modulus.to_string(),
+            u64::MAX.to_string(),
+            format!("{modulus:#x}"),
+            "4294967295:4294967295".to_string(),
+            "-0".to_string(),
+            format!("-{}", modulus / 2 + 1),
+            "4294967296:0".to_string(),
+            "0x".to_string(),
+            "abc".to_string(),
+        ] {
+            assert!(parse_formatted_felt(&value).is_err(), "{value}");
+        }
+    }
+}
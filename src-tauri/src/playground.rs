@@ -0,0 +1,1070 @@
+use crate::artifacts::{decode_program, export_program, ExportRequest, ExportResult};
+use crate::compile::{compile_program, CompileRequest, CompileResult};
+use crate::debug_log::{debug_log, has_debug_decorators, DebugEntry};
+use crate::diagnostics::{collect_diagnostics, Diagnostic};
+use crate::disassembler::{disassemble_program, DisassembleRequest, DisassemblyResult};
+use crate::examples::ExampleCatalog;
+use crate::host::{EventHandler, HostEvent, PlaygroundHost};
+use crate::inputs::{parse_program_inputs, parse_stack_inputs, parse_stack_outputs};
+use crate::limits::{run_with_timeout, EffectiveLimits, LimitOverrides, LIMITS};
+use crate::mast_tree::{mast_tree, MastTreeRequest, MastTreeResult};
+use crate::memory::{memory_report, MemoryQuery, MemoryReport};
+use crate::outputs::{final_stack, format_stack, OutputSettings};
+use crate::profiler::{library_procedures, profile_program, ProfileReport};
+use crate::progress::{interruptible, Cancelled, Phase, Progress};
+use crate::proving::{ProvingOptionsRequest, ProvingOptionsUsed};
+use crate::test_runner::{run_test_suite, TestReport, TestSuite};
+use crate::trace_export::{export_trace, validate_range, TraceExport, TraceRequest};
+use crate::trace_summary::{trace_summary, TraceSummary};
+use miden_stdlib::StdLibrary;
+use miden_vm::{
+    assembly::{
+        ast::{Module, ModuleKind},
+        debuginfo::{Location, SourceManager},
+        DefaultSourceManager, Library, LibraryPath,
+    },
+    execute, prove, verify, AdviceInputs, Assembler, DefaultHost, ExecutionError, ExecutionProof,
+    Kernel, Program, ProgramInfo, StackInputs, Word,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// The engine shared by the Tauri commands and the web server, so both front-ends assemble, run
+/// and prove programs the same way and return the same results.
+pub static PLAYGROUND: Lazy<Playground> = Lazy::new(Playground::default);
+
+#[derive(Deserialize, Default)]
+pub struct ExecutionRequest {
+    #[serde(default)]
+    pub program: String,
+    /// A precompiled program, as returned by `/api/export`, run instead of `program`.
+    pub mast: Option<Vec<u8>>,
+    /// Program inputs in the Miden `.inputs` file format: `operand_stack`, `advice_stack`,
+    /// `advice_map` and `merkle_store`.
+    pub inputs: Option<Value>,
+    /// Library modules the program can import, e.g. `use.myproj::math`.
+    #[serde(default)]
+    pub modules: Vec<ModuleSource>,
+    #[serde(default)]
+    pub settings: ExecutionSettings,
+    /// Security level and hash function used when proving; ignored by execution.
+    #[serde(default)]
+    pub proving_options: ProvingOptionsRequest,
+}
+
+/// A MASM library module supplied alongside the program.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ModuleSource {
+    /// Fully qualified module path such as `myproj::math`; the first component is its namespace.
+    pub path: String,
+    pub source: String,
+}
+
+/// Optional execution features; everything is off unless the request asks for it.
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct ExecutionSettings {
+    /// Re-run the program step by step and report where its cycles were spent.
+    pub profile: bool,
+    /// Tighter cycle and time limits than the server defaults.
+    pub limits: LimitOverrides,
+    /// Report the final memory and the memory access log, filtered by this query.
+    pub memory: Option<MemoryQuery>,
+    /// How many output stack elements to return and how to format them.
+    pub output: OutputSettings,
+    /// Handlers the host runs when the program emits one of their events.
+    pub events: Vec<EventHandler>,
+}
+
+/// The stage a request failed in, so clients can tell limit violations apart from program bugs.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Assembly,
+    Inputs,
+    Execution,
+    Proving,
+    CycleLimitExceeded,
+    Timeout,
+    Cancelled,
+}
+
+#[derive(Serialize)]
+pub struct ExecutionResult {
+    pub success: bool,
+    pub stack_outputs: Option<Vec<String>>,
+    /// Whether `settings.output.depth` cut off part of the output stack.
+    pub stack_truncated: Option<bool>,
+    pub program_hash: Option<String>,
+    pub cycles: Option<u32>,
+    /// How the trace behind `cycles` divides into its segments.
+    pub trace_summary: Option<TraceSummary>,
+    pub error: Option<String>,
+    pub error_kind: Option<ErrorKind>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub profile: Option<ProfileReport>,
+    pub memory: Option<MemoryReport>,
+    /// Every event the program emitted, in order, whether or not a handler was registered for it.
+    pub events: Vec<HostEvent>,
+    /// Whether events were left out of `events`, past the first 1024.
+    pub events_truncated: bool,
+    /// Output of the `debug` and `trace` decorators the program ran, in order.
+    pub debug_log: Vec<DebugEntry>,
+    /// Whether entries were left out of `debug_log`, past the first 1024 or because capturing
+    /// them ran out of time.
+    pub debug_log_truncated: bool,
+    pub compilation_time_ms: Option<f64>,
+    pub execution_time_ms: Option<f64>,
+    pub total_time_ms: Option<f64>,
+}
+
+#[derive(Serialize)]
+pub struct ProofResult {
+    pub success: bool,
+    pub proof_bytes: Option<Vec<u8>>,
+    pub proof_size_bytes: Option<usize>,
+    pub proving_options: Option<ProvingOptionsUsed>,
+    pub program_hash: Option<String>,
+    pub stack_outputs: Option<Vec<String>>,
+    /// Whether `settings.output.depth` cut off part of the output stack.
+    pub stack_truncated: Option<bool>,
+    pub error: Option<String>,
+    pub error_kind: Option<ErrorKind>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub compilation_time_ms: Option<f64>,
+    pub proving_time_ms: Option<f64>,
+    pub total_time_ms: Option<f64>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyRequest {
+    pub program_hash: String,
+    pub inputs: Option<Value>,
+    pub stack_outputs: Vec<String>,
+    pub proof_bytes: Vec<u8>,
+}
+
+#[derive(Serialize)]
+pub struct VerificationResult {
+    pub success: bool,
+    pub program_hash: Option<String>,
+    pub security_level: Option<u32>,
+    pub error: Option<String>,
+    pub verification_time_ms: Option<f64>,
+    pub total_time_ms: Option<f64>,
+}
+
+impl ExecutionResult {
+    /// A failed result carrying only the error; failures further along fill in what they know with
+    /// struct update syntax.
+    pub fn failure(error_kind: ErrorKind, error: String) -> Self {
+        ExecutionResult {
+            success: false,
+            stack_outputs: None,
+            stack_truncated: None,
+            program_hash: None,
+            cycles: None,
+            trace_summary: None,
+            error: Some(error),
+            error_kind: Some(error_kind),
+            diagnostics: Vec::new(),
+            profile: None,
+            memory: None,
+            events: Vec::new(),
+            events_truncated: false,
+            debug_log: Vec::new(),
+            debug_log_truncated: false,
+            compilation_time_ms: None,
+            execution_time_ms: None,
+            total_time_ms: None,
+        }
+    }
+}
+
+impl ProofResult {
+    /// A failed result carrying only the error, like [ExecutionResult::failure].
+    pub fn failure(error_kind: ErrorKind, error: String) -> Self {
+        ProofResult {
+            success: false,
+            proof_bytes: None,
+            proof_size_bytes: None,
+            proving_options: None,
+            program_hash: None,
+            stack_outputs: None,
+            stack_truncated: None,
+            error: Some(error),
+            error_kind: Some(error_kind),
+            diagnostics: Vec::new(),
+            compilation_time_ms: None,
+            proving_time_ms: None,
+            total_time_ms: None,
+        }
+    }
+}
+
+impl VerificationResult {
+    /// A result for a request rejected before it reached the verifier.
+    pub fn failure(error: String) -> Self {
+        VerificationResult {
+            success: false,
+            program_hash: None,
+            security_level: None,
+            error: Some(error),
+            verification_time_ms: None,
+            total_time_ms: None,
+        }
+    }
+}
+
+/// Resolves a byte-offset [Location] recorded by the assembler into a one-indexed line and column.
+pub(crate) fn resolve_source_location(
+    source_manager: &DefaultSourceManager,
+    location: &Location,
+) -> Option<SourceLocation> {
+    let span = source_manager.location_to_span(location.clone())?;
+    let file_line_col = source_manager.file_line_col(span).ok()?;
+
+    Some(SourceLocation {
+        file: file_line_col.uri.to_string(),
+        line: file_line_col.line.to_u32(),
+        column: file_line_col.column.to_u32(),
+    })
+}
+
+/// Why [Playground::assembler] could not produce an assembler.
+pub struct AssemblerError {
+    pub message: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Describes a VM error, reporting cycle limit violations separately from program failures.
+pub(crate) fn classify_execution_error(
+    error: &ExecutionError,
+    failure_kind: ErrorKind,
+) -> (String, ErrorKind) {
+    match error {
+        ExecutionError::CycleLimitExceeded(max_cycles) => (
+            format!("Cycle limit exceeded: the program ran for more than {max_cycles} cycles"),
+            ErrorKind::CycleLimitExceeded,
+        ),
+        _ if failure_kind == ErrorKind::Proving => {
+            (format!("Proving error: {error}"), failure_kind)
+        }
+        _ => (format!("Execution error: {error}"), failure_kind),
+    }
+}
+
+pub(crate) fn timeout_message(stage: &str, limits: &EffectiveLimits) -> String {
+    format!(
+        "Timeout: {stage} did not finish within {} ms",
+        limits.timeout.as_millis()
+    )
+}
+
+/// Parses the user's modules one at a time, so a syntax error can be traced back to its module.
+fn parse_modules(
+    source_manager: &Arc<DefaultSourceManager>,
+    modules: &[ModuleSource],
+) -> Result<Vec<Module>, AssemblerError> {
+    let mut parsed = Vec::with_capacity(modules.len());
+    for module in modules {
+        let path = LibraryPath::new(&module.path).map_err(|e| AssemblerError {
+            message: format!("Invalid module path '{}': {e}", module.path),
+            diagnostics: Vec::new(),
+        })?;
+        let ast = Module::parser(ModuleKind::Library)
+            .parse_str(path, &module.source, source_manager.as_ref())
+            .map_err(|e| AssemblerError {
+                message: format!("Failed to parse module {}: {e}", module.path),
+                diagnostics: collect_diagnostics(e.as_ref()),
+            })?;
+        parsed.push(*ast);
+    }
+    Ok(parsed)
+}
+
+/// Assembles parsed modules into a library, naming the module an error occurred in.
+fn module_library(assembler: &Assembler, modules: Vec<Module>) -> Result<Library, AssemblerError> {
+    assembler.clone().assemble_library(modules).map_err(|e| {
+        let diagnostics = collect_diagnostics(e.as_ref());
+        let message = match diagnostics.iter().find_map(|d| d.file.as_ref()) {
+            Some(file) => format!("Failed to assemble module {file}: {e}"),
+            None => format!("Failed to assemble modules: {e}"),
+        };
+        AssemblerError {
+            message,
+            diagnostics,
+        }
+    })
+}
+
+/// Assembles, executes, proves and verifies programs against a fixed set of libraries.
+pub struct Playground {
+    /// Libraries linked into every program and made available to the host.
+    libraries: Vec<Library>,
+    examples: ExampleCatalog,
+}
+
+impl Default for Playground {
+    fn default() -> Self {
+        Playground {
+            libraries: vec![StdLibrary::default().into()],
+            examples: ExampleCatalog::from_env(),
+        }
+    }
+}
+
+impl Playground {
+    /// Creates a debug-mode assembler with the playground libraries and the user's modules linked in.
+    pub fn assembler(
+        &self,
+        source_manager: &Arc<DefaultSourceManager>,
+        modules: &[ModuleSource],
+    ) -> Result<Assembler, AssemblerError> {
+        let assembler = self.library_assembler(source_manager)?;
+        if modules.is_empty() {
+            return Ok(assembler);
+        }
+
+        let parsed = parse_modules(source_manager, modules)?;
+
+        // Assembling the modules on their own first reports their errors against the module they
+        // occur in, rather than against the program importing it
+        module_library(&assembler, parsed.clone())?;
+
+        // Linking a prebuilt library instead would leave the module decorators in a separate MAST
+        // forest, which the assembler cannot resolve for `call` targets
+        let mut assembler = assembler;
+        assembler
+            .compile_and_statically_link_all(parsed)
+            .map_err(|e| AssemblerError {
+                message: format!("Failed to link modules: {e}"),
+                diagnostics: collect_diagnostics(e.as_ref()),
+            })?;
+
+        Ok(assembler)
+    }
+
+    /// Assembles the user's modules into a library of their own, or returns `None` if there are
+    /// none.
+    pub fn module_library(
+        &self,
+        source_manager: &Arc<DefaultSourceManager>,
+        modules: &[ModuleSource],
+    ) -> Result<Option<Library>, AssemblerError> {
+        if modules.is_empty() {
+            return Ok(None);
+        }
+        let assembler = self.library_assembler(source_manager)?;
+        let parsed = parse_modules(source_manager, modules)?;
+        module_library(&assembler, parsed).map(Some)
+    }
+
+    /// Creates a debug-mode assembler with the playground libraries linked in.
+    fn library_assembler(
+        &self,
+        source_manager: &Arc<DefaultSourceManager>,
+    ) -> Result<Assembler, AssemblerError> {
+        let mut assembler = Assembler::new(source_manager.clone()).with_debug_mode(true);
+        for library in &self.libraries {
+            assembler = assembler
+                .with_static_library(library)
+                .map_err(|e| AssemblerError {
+                    message: format!("Failed to configure assembler: {e}"),
+                    diagnostics: collect_diagnostics(e.as_ref()),
+                })?;
+        }
+        Ok(assembler)
+    }
+
+    /// The libraries linked into every program.
+    pub fn libraries(&self) -> &[Library] {
+        &self.libraries
+    }
+
+    /// Creates a host that can resolve procedures from the playground libraries.
+    pub fn host(&self) -> DefaultHost {
+        let mut host = DefaultHost::default();
+        for library in &self.libraries {
+            host.load_mast_forest(library.mast_forest().clone())
+                .expect("loading a library into the default host cannot fail");
+        }
+        host
+    }
+
+    /// Creates a host that also runs the event handlers of `settings`; see [PlaygroundHost].
+    pub fn event_host(&self, settings: &ExecutionSettings) -> Result<PlaygroundHost, String> {
+        PlaygroundHost::new(self.host(), &settings.events, &settings.output)
+    }
+
+    /// Assembles a program with [Playground::assembler].
+    pub fn assemble(
+        &self,
+        source_manager: &Arc<DefaultSourceManager>,
+        program: &str,
+        modules: &[ModuleSource],
+    ) -> Result<Program, AssemblerError> {
+        self.assembler(source_manager, modules)?
+            .assemble_program(program)
+            .map_err(|e| AssemblerError {
+                message: format!("Assembly error: {e}"),
+                diagnostics: collect_diagnostics(e.as_ref()),
+            })
+    }
+
+    /// Loads a precompiled program, or assembles `program` if there is none.
+    fn load_program(
+        &self,
+        source_manager: &Arc<DefaultSourceManager>,
+        program: &str,
+        modules: &[ModuleSource],
+        mast: Option<&[u8]>,
+    ) -> Result<Program, (AssemblerError, ErrorKind)> {
+        match mast {
+            Some(mast) => decode_program(mast).map_err(|message| {
+                let error = AssemblerError {
+                    message,
+                    diagnostics: Vec::new(),
+                };
+                (error, ErrorKind::Inputs)
+            }),
+            None => self
+                .assemble(source_manager, program, modules)
+                .map_err(|e| (e, ErrorKind::Assembly)),
+        }
+    }
+
+    /// Describes the MAST of a program as a tree; see [mast_tree].
+    pub fn mast_tree(&self, request: &MastTreeRequest) -> MastTreeResult {
+        let source_manager = Arc::new(DefaultSourceManager::default());
+        let program = self.load_program(
+            &source_manager,
+            &request.program,
+            &request.modules,
+            request.mast.as_deref(),
+        );
+        match program {
+            Ok(program) => mast_tree(
+                &program,
+                &source_manager,
+                &library_procedures(&self.libraries),
+            ),
+            Err((e, error_kind)) => MastTreeResult::failure(error_kind, e.message, e.diagnostics),
+        }
+    }
+
+    /// Maps the instructions of a program to the VM operations they were lowered to; see
+    /// [disassemble_program].
+    pub fn disassemble(&self, request: &DisassembleRequest) -> DisassemblyResult {
+        let source_manager = Arc::new(DefaultSourceManager::default());
+        let program = self.load_program(
+            &source_manager,
+            &request.program,
+            &request.modules,
+            request.mast.as_deref(),
+        );
+        match program {
+            Ok(program) => disassemble_program(
+                &program,
+                &source_manager,
+                &library_procedures(&self.libraries),
+            ),
+            Err((e, error_kind)) => {
+                DisassemblyResult::failure(error_kind, e.message, e.diagnostics)
+            }
+        }
+    }
+
+    /// Assembles a program without running it and describes its procedures; see [compile_program].
+    pub fn compile(&self, request: &CompileRequest) -> CompileResult {
+        compile_program(self, request)
+    }
+
+    /// Assembles a program and serializes it; see [export_program].
+    pub fn export(&self, request: &ExportRequest) -> ExportResult {
+        export_program(self, request)
+    }
+
+    /// Executes a program and exports its execution trace; see [export_trace].
+    pub fn trace(&self, request: &TraceRequest) -> TraceExport {
+        self.trace_with_progress(request, &Progress::default())
+    }
+
+    /// Exports an execution trace like [Playground::trace]. A cancelled export stops at the next
+    /// MAST node it executes.
+    pub fn trace_with_progress(&self, request: &TraceRequest, progress: &Progress) -> TraceExport {
+        let execution = &request.execution;
+        let format = request.format;
+        let source_manager = Arc::new(DefaultSourceManager::default());
+
+        let (stack_inputs, advice_inputs) = match &execution.inputs {
+            Some(inputs) => match parse_program_inputs(inputs) {
+                Ok(inputs) => inputs,
+                Err(e) => return TraceExport::failure(format, ErrorKind::Inputs, e, Vec::new()),
+            },
+            None => (StackInputs::default(), AdviceInputs::default()),
+        };
+        if let Err(e) = validate_range(request) {
+            return TraceExport::failure(format, ErrorKind::Inputs, e, Vec::new());
+        }
+        let host = match self.event_host(&execution.settings) {
+            Ok(host) => host,
+            Err(e) => return TraceExport::failure(format, ErrorKind::Inputs, e, Vec::new()),
+        };
+
+        let program = match self.load_program(
+            &source_manager,
+            &execution.program,
+            &execution.modules,
+            execution.mast.as_deref(),
+        ) {
+            Ok(program) => program,
+            Err((e, error_kind)) => {
+                return TraceExport::failure(format, error_kind, e.message, e.diagnostics)
+            }
+        };
+
+        let limits = LIMITS.resolve(&execution.settings.limits);
+        let exec_options = match limits.execution_options() {
+            Ok(options) => options,
+            Err(e) => return TraceExport::failure(format, ErrorKind::Inputs, e, Vec::new()),
+        };
+
+        let mut host = host.with_progress(progress);
+        let trace = run_with_timeout(limits.timeout, progress, || {
+            execute(
+                &interruptible(&program),
+                stack_inputs,
+                advice_inputs,
+                &mut host,
+                exec_options,
+                source_manager,
+            )
+        });
+        match trace {
+            Some(Ok(trace)) => export_trace(&trace, request),
+            Some(Err(_)) if progress.is_cancelled() => TraceExport::failure(
+                format,
+                ErrorKind::Cancelled,
+                "Cancelled".to_string(),
+                Vec::new(),
+            ),
+            Some(Err(e)) => {
+                let (error, error_kind) = classify_execution_error(&e, ErrorKind::Execution);
+                TraceExport::failure(format, error_kind, error, collect_diagnostics(&e))
+            }
+            None => TraceExport::failure(
+                format,
+                ErrorKind::Timeout,
+                timeout_message("execution", &limits),
+                Vec::new(),
+            ),
+        }
+    }
+
+    pub fn examples(&self) -> &ExampleCatalog {
+        &self.examples
+    }
+
+    /// Runs the cases of a test suite in parallel; see [run_test_suite].
+    pub fn test(&self, suite: &TestSuite) -> TestReport {
+        run_test_suite(self, suite, &Progress::default())
+    }
+
+    /// Runs a test suite like [Playground::test], starting no further cases once `progress` is
+    /// cancelled.
+    pub fn test_with_progress(&self, suite: &TestSuite, progress: &Progress) -> TestReport {
+        run_test_suite(self, suite, progress)
+    }
+
+    /// Checks that every example runs and produces its expected stack; see
+    /// [ExampleCatalog::validate].
+    pub fn validate_examples(&self) -> Vec<String> {
+        self.examples.validate(self)
+    }
+
+    pub fn execute(&self, request: &ExecutionRequest) -> ExecutionResult {
+        self.execute_with_progress(request, &Progress::default())
+    }
+
+    /// Executes a program, reporting each phase to `progress`. A cancelled run stops at the next
+    /// MAST node it enters, as does a run that exceeds its timeout.
+    pub fn execute_with_progress(
+        &self,
+        request: &ExecutionRequest,
+        progress: &Progress,
+    ) -> ExecutionResult {
+        let settings = &request.settings;
+        let total_start = Instant::now();
+        let source_manager = Arc::new(DefaultSourceManager::default());
+
+        // Parse and set up stack and advice inputs
+        let (stack_inputs, advice_inputs) = if let Some(inputs) = &request.inputs {
+            match parse_program_inputs(inputs) {
+                Ok(inputs) => inputs,
+                Err(e) => {
+                    return ExecutionResult {
+                        total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                        ..ExecutionResult::failure(ErrorKind::Inputs, e)
+                    }
+                }
+            }
+        } else {
+            (StackInputs::default(), AdviceInputs::default())
+        };
+
+        let host = match self.event_host(settings) {
+            Ok(host) => host,
+            Err(e) => {
+                return ExecutionResult {
+                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                    ..ExecutionResult::failure(ErrorKind::Inputs, e)
+                }
+            }
+        };
+
+        // Assemble the program, unless it was sent precompiled
+        progress.phase(Phase::Assembling);
+        let compilation_start = Instant::now();
+        let program = match self.load_program(
+            &source_manager,
+            &request.program,
+            &request.modules,
+            request.mast.as_deref(),
+        ) {
+            Ok(program) => program,
+            Err((e, error_kind)) => {
+                return ExecutionResult {
+                    diagnostics: e.diagnostics,
+                    compilation_time_ms: Some(compilation_start.elapsed().as_millis() as f64),
+                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                    ..ExecutionResult::failure(error_kind, e.message)
+                }
+            }
+        };
+
+        let compilation_time = compilation_start.elapsed().as_millis() as f64;
+
+        let limits = LIMITS.resolve(&settings.limits);
+        let exec_options = match limits.execution_options() {
+            Ok(options) => options,
+            Err(e) => {
+                return ExecutionResult {
+                    compilation_time_ms: Some(compilation_time),
+                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                    ..ExecutionResult::failure(ErrorKind::Inputs, e)
+                }
+            }
+        };
+
+        if progress.is_cancelled() {
+            return ExecutionResult {
+                compilation_time_ms: Some(compilation_time),
+                total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                ..ExecutionResult::failure(ErrorKind::Cancelled, "Cancelled".to_string())
+            };
+        }
+
+        // Execute the program
+        progress.phase(Phase::Executing);
+        let execution_start = Instant::now();
+        let execution = {
+            let program = program.clone();
+            let stack_inputs = stack_inputs.clone();
+            let advice_inputs = advice_inputs.clone();
+            let source_manager = source_manager.clone();
+            let mut host = host.clone().with_progress(progress);
+            run_with_timeout(limits.timeout, progress, move || {
+                let trace = execute(
+                    &interruptible(&program),
+                    stack_inputs,
+                    advice_inputs,
+                    &mut host,
+                    exec_options,
+                    source_manager,
+                );
+                (trace, host.into_events())
+            })
+        };
+        let (trace, (events, events_truncated)) = match execution {
+            Some((Ok(trace), events)) => (trace, events),
+            Some((Err(_), _)) if progress.is_cancelled() => {
+                return ExecutionResult {
+                    compilation_time_ms: Some(compilation_time),
+                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                    ..ExecutionResult::failure(ErrorKind::Cancelled, "Cancelled".to_string())
+                };
+            }
+            Some((Err(e), (events, events_truncated))) => {
+                let (error, error_kind) = classify_execution_error(&e, ErrorKind::Execution);
+                // A program that ran out of cycles would not stop when replayed
+                let (debug_log, debug_log_truncated) =
+                    if error_kind == ErrorKind::Execution && has_debug_decorators(&program) {
+                        debug_log(
+                            &program,
+                            stack_inputs.clone(),
+                            advice_inputs.clone(),
+                            host.clone(),
+                            source_manager.clone(),
+                            progress,
+                            limits.timeout.saturating_sub(execution_start.elapsed()),
+                        )
+                    } else {
+                        (Vec::new(), false)
+                    };
+                // Show what was left behind when the program ends with too many elements on the
+                // stack, if the replay finishes within what is left of the timeout
+                let stack = match e {
+                    ExecutionError::OutputStackOverflow(_) => final_stack(
+                        &program,
+                        stack_inputs,
+                        advice_inputs,
+                        host.clone(),
+                        source_manager,
+                        progress,
+                        limits.timeout.saturating_sub(execution_start.elapsed()),
+                    )
+                    .map(|stack| format_stack(&stack, &settings.output)),
+                    _ => None,
+                };
+                return ExecutionResult {
+                    stack_truncated: stack.as_ref().map(|stack| stack.truncated),
+                    stack_outputs: stack.map(|stack| stack.elements),
+                    diagnostics: collect_diagnostics(&e),
+                    events,
+                    events_truncated,
+                    debug_log,
+                    debug_log_truncated,
+                    compilation_time_ms: Some(compilation_time),
+                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                    ..ExecutionResult::failure(error_kind, error)
+                };
+            }
+            None => {
+                return ExecutionResult {
+                    compilation_time_ms: Some(compilation_time),
+                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                    ..ExecutionResult::failure(
+                        ErrorKind::Timeout,
+                        timeout_message("execution", &limits),
+                    )
+                };
+            }
+        };
+
+        let execution_time = execution_start.elapsed().as_millis() as f64;
+        progress.cycles(trace.get_trace_len() as u64);
+        let summary = trace_summary(&trace);
+
+        // Execution skips debug decorators, so they are captured by replaying the program within
+        // what is left of the timeout
+        let (debug_log, debug_log_truncated) = if has_debug_decorators(&program) {
+            debug_log(
+                &program,
+                stack_inputs.clone(),
+                advice_inputs.clone(),
+                host.clone(),
+                source_manager.clone(),
+                progress,
+                limits.timeout.saturating_sub(execution_start.elapsed()),
+            )
+        } else {
+            (Vec::new(), false)
+        };
+
+        // Profiling replays the program, so it is kept out of the reported execution time but
+        // shares the timeout
+        let profile = if settings.profile {
+            let profiled = profile_program(
+                &program,
+                stack_inputs,
+                advice_inputs,
+                &mut host.clone().with_progress(progress),
+                source_manager,
+                &library_procedures(&self.libraries),
+                trace.get_trace_len() as u64,
+                progress,
+                limits.timeout.saturating_sub(execution_start.elapsed()),
+            );
+            let profiled = match profiled {
+                Some(report) => report.map_err(|e| (ErrorKind::Execution, e)),
+                None => Err((ErrorKind::Timeout, timeout_message("profiling", &limits))),
+            };
+            match profiled {
+                Ok(report) => Some(report),
+                Err((error_kind, e)) => {
+                    return ExecutionResult {
+                        program_hash: Some(program.hash().to_string()),
+                        cycles: Some(trace.get_trace_len() as u32),
+                        trace_summary: Some(summary.clone()),
+                        events,
+                        events_truncated,
+                        debug_log,
+                        debug_log_truncated,
+                        compilation_time_ms: Some(compilation_time),
+                        execution_time_ms: Some(execution_time),
+                        total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                        ..ExecutionResult::failure(error_kind, e)
+                    }
+                }
+            }
+        } else {
+            None
+        };
+
+        let memory = settings
+            .memory
+            .as_ref()
+            .map(|query| memory_report(&trace, query));
+
+        let total_time = total_start.elapsed().as_millis() as f64;
+
+        let stack = format_stack(trace.stack_outputs().as_slice(), &settings.output);
+
+        progress.phase(Phase::Done);
+        ExecutionResult {
+            success: true,
+            stack_outputs: Some(stack.elements),
+            stack_truncated: Some(stack.truncated),
+            program_hash: Some(program.hash().to_string()),
+            cycles: Some(trace.get_trace_len() as u32),
+            trace_summary: Some(summary),
+            error: None,
+            error_kind: None,
+            diagnostics: Vec::new(),
+            profile,
+            memory,
+            events,
+            events_truncated,
+            debug_log,
+            debug_log_truncated,
+            compilation_time_ms: Some(compilation_time),
+            execution_time_ms: Some(execution_time),
+            total_time_ms: Some(total_time),
+        }
+    }
+
+    pub fn prove(&self, request: &ExecutionRequest) -> ProofResult {
+        self.prove_with_progress(request, &Progress::default())
+    }
+
+    /// Proves a program, reporting each phase to `progress`. A cancelled run stops at the next MAST
+    /// node it executes or the next phase of the prover, as does a run that exceeds its timeout.
+    pub fn prove_with_progress(
+        &self,
+        request: &ExecutionRequest,
+        progress: &Progress,
+    ) -> ProofResult {
+        let settings = &request.settings;
+        let total_start = Instant::now();
+        let source_manager = Arc::new(DefaultSourceManager::default());
+
+        // Parse stack and advice inputs
+        let (stack_inputs, advice_inputs) = if let Some(inputs) = &request.inputs {
+            match parse_program_inputs(inputs) {
+                Ok(inputs) => inputs,
+                Err(e) => {
+                    return ProofResult {
+                        total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                        ..ProofResult::failure(ErrorKind::Inputs, e)
+                    }
+                }
+            }
+        } else {
+            (StackInputs::default(), AdviceInputs::default())
+        };
+
+        // The prover executes the program itself, so it needs the same event handlers
+        let host = match self.event_host(settings) {
+            Ok(host) => host,
+            Err(e) => {
+                return ProofResult {
+                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                    ..ProofResult::failure(ErrorKind::Inputs, e)
+                }
+            }
+        };
+
+        // Assemble the program, unless it was sent precompiled
+        progress.phase(Phase::Assembling);
+        let compilation_start = Instant::now();
+        let program = match self.load_program(
+            &source_manager,
+            &request.program,
+            &request.modules,
+            request.mast.as_deref(),
+        ) {
+            Ok(program) => program,
+            Err((e, error_kind)) => {
+                return ProofResult {
+                    diagnostics: e.diagnostics,
+                    compilation_time_ms: Some(compilation_start.elapsed().as_millis() as f64),
+                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                    ..ProofResult::failure(error_kind, e.message)
+                }
+            }
+        };
+
+        let compilation_time = compilation_start.elapsed().as_millis() as f64;
+
+        let limits = LIMITS.resolve(&settings.limits);
+        let options = limits.execution_options().and_then(|exec_options| {
+            request
+                .proving_options
+                .resolve()
+                .map(|options| options.with_execution_options(exec_options))
+        });
+        let options = match options {
+            Ok(options) => options,
+            Err(e) => {
+                return ProofResult {
+                    compilation_time_ms: Some(compilation_time),
+                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                    ..ProofResult::failure(ErrorKind::Inputs, e)
+                }
+            }
+        };
+
+        // Generate proof; the prover executes the program before building the trace
+        progress.phase(Phase::Executing);
+        let proving_start = Instant::now();
+        let mut host = host.with_progress(progress);
+        let proving = {
+            let program = program.clone();
+            run_with_timeout(limits.timeout, progress, move || {
+                progress.run(|| {
+                    prove(
+                        &interruptible(&program),
+                        stack_inputs,
+                        advice_inputs,
+                        &mut host,
+                        options,
+                        source_manager,
+                    )
+                })
+            })
+        };
+        let (outputs, proof) = match proving {
+            Some(Ok(Ok(result))) => result,
+            Some(Ok(Err(e))) if !progress.is_cancelled() => {
+                let (error, error_kind) = classify_execution_error(&e, ErrorKind::Proving);
+                return ProofResult {
+                    diagnostics: collect_diagnostics(&e),
+                    compilation_time_ms: Some(compilation_time),
+                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                    ..ProofResult::failure(error_kind, error)
+                };
+            }
+            // The prover was stopped, either at a phase or in the middle of execution
+            Some(Err(Cancelled)) | Some(Ok(Err(_))) => {
+                return ProofResult {
+                    compilation_time_ms: Some(compilation_time),
+                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                    ..ProofResult::failure(ErrorKind::Cancelled, "Cancelled".to_string())
+                };
+            }
+            None => {
+                return ProofResult {
+                    compilation_time_ms: Some(compilation_time),
+                    total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+                    ..ProofResult::failure(ErrorKind::Timeout, timeout_message("proving", &limits))
+                };
+            }
+        };
+
+        let proving_time = proving_start.elapsed().as_millis() as f64;
+        let total_time = total_start.elapsed().as_millis() as f64;
+
+        let stack = format_stack(outputs.as_slice(), &settings.output);
+
+        let proof_bytes = proof.to_bytes();
+        progress.phase(Phase::Done);
+        ProofResult {
+            success: true,
+            proof_size_bytes: Some(proof_bytes.len()),
+            proving_options: Some(ProvingOptionsUsed::new(
+                request.proving_options.security_level(),
+                &proof,
+            )),
+            proof_bytes: Some(proof_bytes),
+            program_hash: Some(program.hash().to_string()),
+            stack_outputs: Some(stack.elements),
+            stack_truncated: Some(stack.truncated),
+            error: None,
+            error_kind: None,
+            diagnostics: Vec::new(),
+            compilation_time_ms: Some(compilation_time),
+            proving_time_ms: Some(proving_time),
+            total_time_ms: Some(total_time),
+        }
+    }
+
+    pub fn verify(&self, request: &VerifyRequest) -> VerificationResult {
+        let total_start = Instant::now();
+        let program_hash = request.program_hash.as_str();
+
+        let failure = |error: String| VerificationResult {
+            success: false,
+            program_hash: Some(program_hash.to_string()),
+            security_level: None,
+            error: Some(error),
+            verification_time_ms: None,
+            total_time_ms: Some(total_start.elapsed().as_millis() as f64),
+        };
+
+        let hash = match Word::try_from(program_hash) {
+            Ok(hash) => hash,
+            Err(e) => return failure(format!("Invalid program hash '{program_hash}': {e}")),
+        };
+
+        let stack_inputs = if let Some(inputs) = &request.inputs {
+            match parse_stack_inputs(inputs) {
+                Ok(inputs) => inputs,
+                Err(e) => return failure(e),
+            }
+        } else {
+            StackInputs::default()
+        };
+
+        let stack_outputs = match parse_stack_outputs(&request.stack_outputs) {
+            Ok(outputs) => outputs,
+            Err(e) => return failure(e),
+        };
+
+        let proof = match ExecutionProof::from_bytes(&request.proof_bytes) {
+            Ok(proof) => proof,
+            Err(e) => return failure(format!("Invalid proof: {e}")),
+        };
+
+        // Programs are assembled without a kernel, so the default one is used here as well
+        let program_info = ProgramInfo::new(hash, Kernel::default());
+
+        let verification_start = Instant::now();
+        let security_level = match verify(program_info, stack_inputs, stack_outputs, proof) {
+            Ok(level) => level,
+            Err(e) => return failure(format!("Verification error: {e}")),
+        };
+
+        let verification_time = verification_start.elapsed().as_millis() as f64;
+        let total_time = total_start.elapsed().as_millis() as f64;
+
+        VerificationResult {
+            success: true,
+            program_hash: Some(hash.to_string()),
+            security_level: Some(security_level),
+            error: None,
+            verification_time_ms: Some(verification_time),
+            total_time_ms: Some(total_time),
+        }
+    }
+}
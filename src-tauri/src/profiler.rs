@@ -0,0 +1,694 @@
+use crate::host::PlaygroundHost;
+use crate::limits::run_with_timeout;
+use crate::progress::{interruptible, Progress};
+use miden_core::{
+    mast::{MastForest, MastNode, MastNodeId},
+    Decorator, Operation, ONE, ZERO,
+};
+use miden_vm::{
+    assembly::{DefaultSourceManager, Library},
+    execute_iter,
+    math::Felt,
+    AdviceInputs, Program, StackInputs, VmStateIterator, Word,
+};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Frame charged for library code that was inlined into its caller without debug information.
+const INLINED_LIBRARY_FRAME: &str = "std::<inlined>";
+
+#[derive(Serialize)]
+pub struct ProcedureProfile {
+    pub name: String,
+    pub stdlib: bool,
+    pub calls: u64,
+    pub self_cycles: u64,
+    pub inclusive_cycles: u64,
+}
+
+#[derive(Serialize)]
+pub struct BlockProfile {
+    pub kind: String,
+    pub procedure: String,
+    pub digest: String,
+    pub executions: u64,
+    pub self_cycles: u64,
+    pub inclusive_cycles: u64,
+}
+
+#[derive(Serialize)]
+pub struct ProfileReport {
+    /// Number of cycles actually executed, before padding the trace to a power of two.
+    pub cycles: u64,
+    pub padded_cycles: u64,
+    pub procedures: Vec<ProcedureProfile>,
+    pub blocks: Vec<BlockProfile>,
+    /// Cycles per procedure call stack in the folded format read by `flamegraph.pl` and inferno.
+    pub folded_stacks: String,
+}
+
+/// A single executed operation, as needed to replay it against the MAST.
+struct ExecutedOp {
+    op: Operation,
+    /// Value on top of the stack before the operation ran, which decides SPLIT and LOOP.
+    stack_top: Felt,
+    /// Procedure the assembly instruction behind this operation was written in.
+    context: Option<usize>,
+}
+
+/// What the MAST replay learned about an executed operation.
+struct ReplayedOp {
+    /// Innermost MAST procedure the operation was executed in.
+    procedure: usize,
+    /// Whether the operation's basic block carries assembly decorators. The VM keeps reporting
+    /// the last instruction it saw while running blocks without them, so such context is stale.
+    has_debug_info: bool,
+}
+
+#[derive(Default)]
+struct Names {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+}
+
+impl Names {
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), self.names.len() - 1);
+        self.names.len() - 1
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct BlockKey {
+    kind: &'static str,
+    digest: Word,
+    procedure: usize,
+}
+
+#[derive(Default)]
+struct FrameStats {
+    entries: u64,
+    self_cycles: u64,
+    inclusive_cycles: u64,
+}
+
+/// Adds each sample to the self count of its innermost frame and the inclusive count of every
+/// distinct frame on its stack.
+fn tally(stats: &mut [FrameStats], samples: &HashMap<Vec<usize>, u64>) {
+    for (stack, cycles) in samples {
+        if let Some(&innermost) = stack.last() {
+            stats[innermost].self_cycles += cycles;
+        }
+        for &id in stack.iter().collect::<HashSet<_>>() {
+            stats[id].inclusive_cycles += cycles;
+        }
+    }
+}
+
+/// Replays the executed operations against the program's MAST, keeping track of which control
+/// block and MAST procedure every cycle was spent in.
+struct BlockReplay<'a> {
+    forest: &'a MastForest,
+    entrypoint: MastNodeId,
+    library_procedures: &'a HashMap<Word, String>,
+    ops: &'a [ExecutedOp],
+    cursor: usize,
+    names: &'a mut Names,
+    blocks: Vec<BlockKey>,
+    block_ids: HashMap<BlockKey, usize>,
+    stats: Vec<FrameStats>,
+    block_stack: Vec<usize>,
+    samples: HashMap<Vec<usize>, u64>,
+    /// MAST procedures entered so far, innermost last.
+    procedures: Vec<usize>,
+    /// Whether the basic block being replayed was assembled with debug information.
+    in_debug_block: bool,
+    replayed: Vec<ReplayedOp>,
+}
+
+impl BlockReplay<'_> {
+    fn enter(&mut self, key: BlockKey) {
+        let id = match self.block_ids.get(&key) {
+            Some(&id) => id,
+            None => {
+                let id = self.blocks.len();
+                self.blocks.push(key.clone());
+                self.block_ids.insert(key, id);
+                self.stats.push(FrameStats::default());
+                id
+            }
+        };
+        self.stats[id].entries += 1;
+        self.block_stack.push(id);
+    }
+
+    fn next_op(&mut self) -> Result<(Operation, Felt), String> {
+        let executed = self.ops.get(self.cursor).ok_or_else(|| {
+            "Profiler error: execution ended before the program MAST was fully replayed".to_string()
+        })?;
+        self.cursor += 1;
+        *self.samples.entry(self.block_stack.clone()).or_default() += 1;
+        self.replayed.push(ReplayedOp {
+            procedure: *self
+                .procedures
+                .last()
+                .expect("the entrypoint is a procedure"),
+            has_debug_info: self.in_debug_block,
+        });
+        Ok((executed.op, executed.stack_top))
+    }
+
+    fn expect_op(
+        &mut self,
+        expected: &str,
+        matches: impl Fn(&Operation) -> bool,
+    ) -> Result<Felt, String> {
+        let (op, stack_top) = self.next_op()?;
+        if !matches(&op) {
+            return Err(format!(
+                "Profiler error: expected {expected} at cycle {}, found {op}",
+                self.cursor
+            ));
+        }
+        Ok(stack_top)
+    }
+
+    fn walk(&mut self, node_id: MastNodeId) -> Result<(), String> {
+        let forest = self.forest;
+        let node = &forest[node_id];
+        let stack_depth = self.block_stack.len();
+        let procedure_depth = self.procedures.len();
+
+        let is_entrypoint = node_id == self.entrypoint;
+        if is_entrypoint || forest.is_procedure_root(node_id) {
+            let name = match self.library_procedures.get(&node.digest()) {
+                Some(name) => name.clone(),
+                None => procedure_name(forest, node_id, is_entrypoint),
+            };
+            let id = self.names.intern(&name);
+            self.procedures.push(id);
+        }
+
+        let procedure = *self
+            .procedures
+            .last()
+            .expect("the entrypoint is a procedure");
+        self.enter(BlockKey {
+            kind: block_kind(node),
+            digest: node.digest(),
+            procedure,
+        });
+
+        match node {
+            MastNode::Block(block) => {
+                self.expect_op("SPAN", |op| matches!(op, Operation::Span))?;
+                self.in_debug_block = block
+                    .decorators()
+                    .iter()
+                    .any(|(_, id)| matches!(forest[*id], Decorator::AsmOp(_)));
+                while !matches!(self.next_op()?.0, Operation::End) {}
+                self.in_debug_block = false;
+            }
+            MastNode::Join(join) => {
+                self.expect_op("JOIN", |op| matches!(op, Operation::Join))?;
+                self.walk(join.first())?;
+                self.walk(join.second())?;
+                self.expect_op("END", |op| matches!(op, Operation::End))?;
+            }
+            MastNode::Split(split) => {
+                let condition = self.expect_op("SPLIT", |op| matches!(op, Operation::Split))?;
+                if condition == ONE {
+                    self.walk(split.on_true())?;
+                } else {
+                    self.walk(split.on_false())?;
+                }
+                self.expect_op("END", |op| matches!(op, Operation::End))?;
+            }
+            MastNode::Loop(loop_node) => {
+                let condition = self.expect_op("LOOP", |op| matches!(op, Operation::Loop))?;
+                if condition == ONE {
+                    loop {
+                        self.walk(loop_node.body())?;
+                        match self.next_op()?.0 {
+                            Operation::Repeat => continue,
+                            Operation::End => break,
+                            op => {
+                                let cycle = self.cursor;
+                                return Err(format!(
+                                    "Profiler error: expected REPEAT at cycle {cycle}, found {op}"
+                                ));
+                            }
+                        }
+                    }
+                } else {
+                    self.expect_op("END", |op| matches!(op, Operation::End))?;
+                }
+            }
+            MastNode::Call(call) => {
+                self.expect_op("CALL", |op| {
+                    matches!(op, Operation::Call | Operation::SysCall)
+                })?;
+                self.walk(call.callee())?;
+                self.expect_op("END", |op| matches!(op, Operation::End))?;
+            }
+            MastNode::Dyn(_) => {
+                // The callee is only known at runtime, so its cycles are attributed to this block
+                self.expect_op("DYN", |op| {
+                    matches!(op, Operation::Dyn | Operation::Dyncall)
+                })?;
+                self.skip_block()?;
+                self.expect_op("END", |op| matches!(op, Operation::End))?;
+            }
+            MastNode::External(_) => self.skip_block()?,
+        }
+
+        self.block_stack.truncate(stack_depth);
+        self.procedures.truncate(procedure_depth);
+        Ok(())
+    }
+
+    /// Consumes one complete control block without resolving its MAST node.
+    fn skip_block(&mut self) -> Result<(), String> {
+        let mut depth = 0usize;
+        loop {
+            match self.next_op()?.0 {
+                Operation::Join
+                | Operation::Split
+                | Operation::Loop
+                | Operation::Span
+                | Operation::Call
+                | Operation::SysCall
+                | Operation::Dyn
+                | Operation::Dyncall => depth += 1,
+                Operation::End => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn finish(mut self) -> (Vec<BlockProfile>, Vec<ReplayedOp>) {
+        tally(&mut self.stats, &self.samples);
+
+        let names = &self.names.names;
+        let mut blocks: Vec<BlockProfile> = self
+            .blocks
+            .into_iter()
+            .zip(self.stats)
+            .map(|(key, stats)| BlockProfile {
+                kind: key.kind.to_string(),
+                procedure: names[key.procedure].clone(),
+                digest: key.digest.to_string(),
+                executions: stats.entries,
+                self_cycles: stats.self_cycles,
+                inclusive_cycles: stats.inclusive_cycles,
+            })
+            .collect();
+        blocks.sort_by_key(|block| Reverse(block.inclusive_cycles));
+
+        (blocks, self.replayed)
+    }
+}
+
+/// Attributes cycles to procedures, following the assembly context of each instruction.
+///
+/// Procedures entered through the MAST always get a frame of their own, but `exec` inlines the
+/// callee into the caller's MAST, so inlined procedures are told apart by the context recorded
+/// with each instruction instead. Procedures without instructions of their own never show up,
+/// back-to-back inlined invocations of the same procedure count as a single call, and an
+/// inlined procedure that runs before any instruction of its caller is not nested under it. Library
+/// code carries no context and is charged to the library procedure it was called through, or
+/// to a shared frame when it was inlined.
+fn profile_procedures(
+    ops: &[ExecutedOp],
+    replayed: &[ReplayedOp],
+    names: &mut Names,
+    library_procedures: &HashMap<Word, String>,
+) -> (Vec<ProcedureProfile>, String) {
+    let library_names: HashSet<&str> = library_procedures.values().map(String::as_str).collect();
+    let inlined_library = names.intern(INLINED_LIBRARY_FRAME);
+    let mut stats: Vec<FrameStats> = names.names.iter().map(|_| FrameStats::default()).collect();
+    let mut samples: HashMap<Vec<usize>, u64> = HashMap::new();
+    let mut frames = vec![replayed[0].procedure];
+    stats[replayed[0].procedure].entries += 1;
+
+    let mut mast_procedure = replayed[0].procedure;
+
+    for (executed, replayed) in ops.iter().zip(replayed) {
+        // Entering or leaving a procedure in the MAST is certain, whatever the contexts say
+        if replayed.procedure != mast_procedure {
+            mast_procedure = replayed.procedure;
+            enter_frame(&mut frames, &mut stats, mast_procedure);
+        }
+
+        let procedure = if is_control_flow(&executed.op) {
+            None
+        } else if let Some(context) = executed.context.filter(|_| replayed.has_debug_info) {
+            Some(context)
+        } else if library_names.contains(names.names[replayed.procedure].as_str()) {
+            Some(replayed.procedure)
+        } else {
+            Some(inlined_library)
+        };
+
+        if let Some(procedure) = procedure {
+            enter_frame(&mut frames, &mut stats, procedure);
+        }
+        *samples.entry(frames.clone()).or_default() += 1;
+    }
+
+    tally(&mut stats, &samples);
+
+    let mut folded = BTreeMap::new();
+    for (stack, cycles) in &samples {
+        let labels: Vec<&str> = stack.iter().map(|&id| names.names[id].as_str()).collect();
+        *folded.entry(labels.join(";")).or_insert(0) += cycles;
+    }
+    let folded_stacks = folded
+        .into_iter()
+        .map(|(stack, cycles)| format!("{stack} {cycles}\n"))
+        .collect();
+
+    let mut procedures: Vec<ProcedureProfile> = names
+        .names
+        .iter()
+        .zip(stats)
+        .filter(|(_, stats)| stats.entries > 0)
+        .map(|(name, stats)| ProcedureProfile {
+            name: name.clone(),
+            stdlib: name.starts_with("std::"),
+            calls: stats.entries,
+            self_cycles: stats.self_cycles,
+            inclusive_cycles: stats.inclusive_cycles,
+        })
+        .collect();
+    procedures.sort_by_key(|procedure| Reverse(procedure.inclusive_cycles));
+
+    (procedures, folded_stacks)
+}
+
+/// Makes `procedure` the innermost frame. Same reasoning as the debugger: returning to a
+/// procedure already on the stack means every frame above it has completed.
+fn enter_frame(frames: &mut Vec<usize>, stats: &mut [FrameStats], procedure: usize) {
+    match frames.iter().rposition(|&frame| frame == procedure) {
+        Some(idx) => frames.truncate(idx + 1),
+        None => {
+            frames.push(procedure);
+            stats[procedure].entries += 1;
+        }
+    }
+}
+
+/// Returns true for operations the VM inserts around code blocks rather than assembling from
+/// instructions.
+fn is_control_flow(op: &Operation) -> bool {
+    matches!(
+        op,
+        Operation::Join
+            | Operation::Split
+            | Operation::Loop
+            | Operation::Repeat
+            | Operation::Span
+            | Operation::Respan
+            | Operation::End
+            | Operation::Call
+            | Operation::SysCall
+            | Operation::Dyn
+            | Operation::Dyncall
+            | Operation::Halt
+            | Operation::Noop
+    )
+}
+
+pub(crate) fn block_kind(node: &MastNode) -> &'static str {
+    match node {
+        MastNode::Block(_) => "span",
+        MastNode::Join(_) => "join",
+        MastNode::Split(_) => "split",
+        MastNode::Loop(_) => "loop",
+        MastNode::Call(call) if call.is_syscall() => "syscall",
+        MastNode::Call(_) => "call",
+        MastNode::Dyn(dyn_node) if dyn_node.is_dyncall() => "dyncall",
+        MastNode::Dyn(_) => "dyn",
+        MastNode::External(_) => "external",
+    }
+}
+
+/// Lists the distinct assembly contexts found in the body of the procedure rooted at `root`, in
+/// program order.
+fn body_contexts(forest: &MastForest, root: MastNodeId) -> Vec<&str> {
+    let mut pending = vec![root];
+    let mut contexts = Vec::new();
+
+    while let Some(node_id) = pending.pop() {
+        let node = &forest[node_id];
+        let decorators = node.before_enter().iter().copied().chain(
+            node.get_basic_block()
+                .into_iter()
+                .flat_map(|block| block.decorators().iter().map(|(_, id)| *id)),
+        );
+        for decorator_id in decorators {
+            if let Decorator::AsmOp(asmop) = &forest[decorator_id] {
+                let name = asmop.context_name();
+                if !contexts.contains(&name) {
+                    contexts.push(name);
+                }
+            }
+        }
+
+        // Children are pushed in reverse so the first child in program order is searched first
+        match node {
+            MastNode::Join(join) => pending.extend([join.second(), join.first()]),
+            MastNode::Split(split) => pending.extend([split.on_false(), split.on_true()]),
+            MastNode::Loop(loop_node) => pending.push(loop_node.body()),
+            _ => {}
+        }
+    }
+
+    contexts
+}
+
+/// Names a procedure after an assembly context found in its body.
+///
+/// Callees inlined with `exec` bring their own contexts along, so the entrypoint prefers `$main`
+/// and other procedures the context that shows up in the fewest procedure bodies: the callee's
+/// context also appears in the callee's own body.
+pub(crate) fn procedure_name(forest: &MastForest, root: MastNodeId, is_entrypoint: bool) -> String {
+    let contexts = body_contexts(forest, root);
+    if is_entrypoint {
+        if let Some(main) = contexts.iter().find(|name| name.ends_with("::$main")) {
+            return main.to_string();
+        }
+    }
+    if contexts.len() > 1 {
+        let mut bodies = HashMap::new();
+        for &other in forest.procedure_roots() {
+            for name in body_contexts(forest, other) {
+                *bodies.entry(name).or_insert(0) += 1;
+            }
+        }
+        // `min_by_key` keeps the first of equally shared contexts
+        if let Some(name) = contexts.iter().min_by_key(|name| bodies.get(*name)) {
+            return name.to_string();
+        }
+    }
+
+    contexts
+        .first()
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("proc.{}", forest[root].digest()))
+}
+
+/// Maps the MAST root of every procedure exported by `libraries` to its fully qualified name.
+pub fn library_procedures<'a>(
+    libraries: impl IntoIterator<Item = &'a Library>,
+) -> HashMap<Word, String> {
+    let mut procedures = HashMap::new();
+    for library in libraries {
+        for name in library.exports() {
+            let node_id = library.get_export_node_id(name);
+            procedures.insert(library.mast_forest()[node_id].digest(), name.to_string());
+        }
+    }
+    procedures
+}
+
+/// Re-executes `program` step by step and attributes every cycle to a procedure and control block.
+/// Returns `None` if re-executing it takes longer than `timeout`, which `host` must
+/// [watch `progress`](PlaygroundHost::with_progress) for.
+#[allow(clippy::too_many_arguments)]
+pub fn profile_program(
+    program: &Program,
+    stack_inputs: StackInputs,
+    advice_inputs: AdviceInputs,
+    host: &mut PlaygroundHost,
+    source_manager: Arc<DefaultSourceManager>,
+    library_procedures: &HashMap<Word, String>,
+    padded_cycles: u64,
+    progress: &Progress,
+    timeout: Duration,
+) -> Option<Result<ProfileReport, String>> {
+    let states = run_with_timeout(timeout, progress, || {
+        execute_iter(
+            &interruptible(program),
+            stack_inputs,
+            advice_inputs,
+            host,
+            source_manager,
+        )
+    })?;
+    Some(profile_states(
+        program,
+        states,
+        library_procedures,
+        padded_cycles,
+    ))
+}
+
+fn profile_states(
+    program: &Program,
+    states: VmStateIterator,
+    library_procedures: &HashMap<Word, String>,
+    padded_cycles: u64,
+) -> Result<ProfileReport, String> {
+    let mut names = Names::default();
+    let mut ops = Vec::new();
+    let mut stack_top = ZERO;
+
+    for state in states {
+        let state = state.map_err(|e| format!("Execution error: {e}"))?;
+        if let Some(op) = state.op {
+            ops.push(ExecutedOp {
+                op,
+                stack_top,
+                context: state
+                    .asmop
+                    .as_ref()
+                    .map(|asmop| names.intern(asmop.context_name())),
+            });
+        }
+        stack_top = state.stack.first().copied().unwrap_or(ZERO);
+    }
+
+    let mut replay = BlockReplay {
+        forest: program.mast_forest(),
+        entrypoint: program.entrypoint(),
+        library_procedures,
+        ops: &ops,
+        cursor: 0,
+        names: &mut names,
+        blocks: Vec::new(),
+        block_ids: HashMap::new(),
+        stats: Vec::new(),
+        block_stack: Vec::new(),
+        samples: HashMap::new(),
+        procedures: Vec::new(),
+        in_debug_block: false,
+        replayed: Vec::new(),
+    };
+
+    replay.walk(program.entrypoint())?;
+    if replay.cursor != ops.len() {
+        return Err(format!(
+            "Profiler error: {} cycles were executed after the program MAST ended",
+            ops.len() - replay.cursor
+        ));
+    }
+
+    let (blocks, replayed) = replay.finish();
+    let (procedures, folded_stacks) =
+        profile_procedures(&ops, &replayed, &mut names, library_procedures);
+
+    Ok(ProfileReport {
+        cycles: ops.len() as u64,
+        padded_cycles,
+        procedures,
+        blocks,
+        folded_stacks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::playground::{ExecutionRequest, PLAYGROUND};
+
+    fn profile(program: &str) -> ProfileReport {
+        let mut request = ExecutionRequest {
+            program: program.to_string(),
+            ..Default::default()
+        };
+        request.settings.profile = true;
+        let result = PLAYGROUND.execute(&request);
+        assert!(result.success, "{:?}", result.error);
+        result.profile.expect("profiling was requested")
+    }
+
+    fn procedure<'a>(report: &'a ProfileReport, name: &str) -> &'a ProcedureProfile {
+        report
+            .procedures
+            .iter()
+            .find(|procedure| procedure.name == name)
+            .unwrap_or_else(|| panic!("{name} was not profiled"))
+    }
+
+    #[test]
+    fn inlined_callees_are_nested_under_their_callers() {
+        let report = profile(
+            "proc.inner\n    push.1 add\nend\n\nproc.outer\n    exec.inner\n    exec.inner\n    mul.2\nend\n\nbegin\n    push.3\n    call.outer\n    call.outer\n    swap drop\nend\n",
+        );
+
+        let main = procedure(&report, "$exec::$main");
+        let outer = procedure(&report, "$exec::outer");
+        let inner = procedure(&report, "$exec::inner");
+        assert_eq!((main.calls, outer.calls, inner.calls), (1, 2, 2));
+        assert_eq!(main.inclusive_cycles, report.cycles);
+        assert_eq!(
+            outer.inclusive_cycles,
+            outer.self_cycles + inner.inclusive_cycles
+        );
+        assert_eq!(inner.inclusive_cycles, inner.self_cycles);
+        assert_eq!(
+            report.procedures.iter().map(|p| p.self_cycles).sum::<u64>(),
+            report.cycles
+        );
+        assert!(report
+            .folded_stacks
+            .contains("$exec::$main;$exec::outer;$exec::inner "));
+    }
+
+    #[test]
+    fn every_call_counts_and_blocks_add_up() {
+        let report = profile("proc.square\n    dup mul\nend\n\nbegin\n    push.2\n    repeat.3\n        call.square\n    end\n    drop\nend\n");
+
+        let main = procedure(&report, "$exec::$main");
+        let square = procedure(&report, "$exec::square");
+        assert_eq!(square.calls, 3);
+        assert_eq!(
+            main.inclusive_cycles,
+            main.self_cycles + square.inclusive_cycles
+        );
+        assert_eq!(main.inclusive_cycles, report.cycles);
+
+        let folded: u64 = report
+            .folded_stacks
+            .lines()
+            .map(|line| line.rsplit_once(' ').unwrap().1.parse::<u64>().unwrap())
+            .sum();
+        assert_eq!(folded, report.cycles);
+        assert_eq!(
+            report.blocks.iter().map(|b| b.self_cycles).sum::<u64>(),
+            report.cycles
+        );
+        assert_eq!(report.blocks[0].inclusive_cycles, report.cycles);
+    }
+}
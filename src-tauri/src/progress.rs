@@ -0,0 +1,267 @@
+use crate::debug_log::DebugEntry;
+use miden_core::mast::MastNodeId;
+use miden_core::Decorator;
+use miden_vm::Program;
+use serde::Serialize;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Dispatch, Event, Metadata, Subscriber};
+
+/// The stages a run goes through, in order; proofs pass through every one of them.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    Assembling,
+    Executing,
+    BuildingTrace,
+    Committing,
+    Fri,
+    Done,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct PhaseTiming {
+    pub phase: Phase,
+    pub duration_ms: u64,
+}
+
+/// Reported each time a run enters a new phase.
+#[derive(Serialize, Clone, Debug)]
+pub struct ProgressEvent {
+    pub phase: Phase,
+    /// Cycles executed, known once execution has finished. Proofs report the padded trace length.
+    pub cycles: Option<u64>,
+    pub elapsed_ms: u64,
+    /// How long each of the finished phases took.
+    pub phases: Vec<PhaseTiming>,
+}
+
+/// What a run reports to the sink of its [Progress].
+#[derive(Clone, Debug)]
+pub enum ProgressUpdate {
+    Phase(ProgressEvent),
+    /// The output of a `debug` or `trace` decorator.
+    Debug(DebugEntry),
+}
+
+/// Returned by [Progress::run] when the run was cancelled before it finished.
+#[derive(Debug)]
+pub struct Cancelled;
+
+/// The ID of the `trace` decorator [interruptible] adds; the host checks for cancellation when it
+/// sees it rather than recording it.
+pub const CANCEL_CHECK_TRACE_ID: u32 = u32::MAX;
+
+/// Returns a copy of `program` that gives the host a chance to stop it on entering each of its
+/// MAST nodes, including every iteration of a loop. Decorators are not part of the MAST, so the
+/// program hash stays the same. The VM only runs the checks with tracing enabled; see
+/// [crate::limits::EffectiveLimits::execution_options].
+pub fn interruptible(program: &Program) -> Program {
+    let mut forest = program.mast_forest().as_ref().clone();
+    let check = forest
+        .add_decorator(Decorator::Trace(CANCEL_CHECK_TRACE_ID))
+        .expect("a program cannot use every decorator ID");
+    for index in 0..forest.num_nodes() {
+        let node_id = MastNodeId::from_u32_safe(index, &forest).expect("node IDs are contiguous");
+        forest.append_before_enter(node_id, &[check]);
+    }
+    Program::with_kernel(
+        Arc::new(forest),
+        program.entrypoint(),
+        program.kernel().clone(),
+    )
+}
+
+type Sink = Box<dyn Fn(ProgressUpdate) + Send + Sync>;
+
+struct TrackerState {
+    phase: Option<Phase>,
+    phase_start: Instant,
+    cycles: Option<u64>,
+    phases: Vec<PhaseTiming>,
+}
+
+struct Tracker {
+    start: Instant,
+    state: Mutex<TrackerState>,
+    sink: Sink,
+}
+
+/// Reports the phases of a run and lets it be cancelled. The default value reports nothing, but
+/// can still be cancelled, e.g. when the run times out.
+#[derive(Clone, Default)]
+pub struct Progress {
+    cancelled: Arc<AtomicBool>,
+    tracker: Option<Arc<Tracker>>,
+}
+
+impl Progress {
+    /// Sends every phase transition and debug entry to `sink`; setting `cancelled` has the same
+    /// effect as [Progress::cancel].
+    pub fn new(
+        cancelled: Arc<AtomicBool>,
+        sink: impl Fn(ProgressUpdate) + Send + Sync + 'static,
+    ) -> Self {
+        let now = Instant::now();
+        Progress {
+            cancelled,
+            tracker: Some(Arc::new(Tracker {
+                start: now,
+                state: Mutex::new(TrackerState {
+                    phase: None,
+                    phase_start: now,
+                    cycles: None,
+                    phases: Vec::new(),
+                }),
+                sink: Box::new(sink),
+            })),
+        }
+    }
+
+    /// Enters `phase`, ignoring phases the run has already reached.
+    pub fn phase(&self, phase: Phase) {
+        let Some(tracker) = &self.tracker else {
+            return;
+        };
+        let event = {
+            let mut state = tracker.state.lock().unwrap();
+            if state.phase >= Some(phase) {
+                return;
+            }
+            let now = Instant::now();
+            if let Some(previous) = state.phase {
+                let duration_ms = now.duration_since(state.phase_start).as_millis() as u64;
+                state.phases.push(PhaseTiming {
+                    phase: previous,
+                    duration_ms,
+                });
+            }
+            state.phase = Some(phase);
+            state.phase_start = now;
+            ProgressEvent {
+                phase,
+                cycles: state.cycles,
+                elapsed_ms: now.duration_since(tracker.start).as_millis() as u64,
+                phases: state.phases.clone(),
+            }
+        };
+        (tracker.sink)(ProgressUpdate::Phase(event));
+    }
+
+    pub fn debug(&self, entry: DebugEntry) {
+        if let Some(tracker) = &self.tracker {
+            (tracker.sink)(ProgressUpdate::Debug(entry));
+        }
+    }
+
+    /// Records the cycle count, which is reported with the next phase.
+    pub fn cycles(&self, cycles: u64) {
+        if let Some(tracker) = &self.tracker {
+            tracker.state.lock().unwrap().cycles = Some(cycles);
+        }
+    }
+
+    /// Stops the run: the prover at its next phase, and execution of an [interruptible] program at
+    /// its next MAST node.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Runs the prover, following its phases through the tracing spans it opens. Cancelling the
+    /// run unwinds out of `task` when it opens its next span.
+    pub fn run<T>(&self, task: impl FnOnce() -> T) -> Result<T, Cancelled> {
+        if self.is_cancelled() {
+            return Err(Cancelled);
+        }
+
+        let dispatch = Dispatch::new(PhaseSubscriber {
+            progress: self.clone(),
+            next_id: AtomicU64::new(1),
+        });
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            tracing::dispatcher::with_default(&dispatch, task)
+        }));
+        match outcome {
+            Ok(value) => Ok(value),
+            Err(payload) if payload.is::<Cancelled>() => Err(Cancelled),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+/// Maps the spans opened by the STARK prover to playground phases.
+struct PhaseSubscriber {
+    progress: Progress,
+    next_id: AtomicU64,
+}
+
+fn span_phase(name: &str) -> Option<Phase> {
+    match name {
+        "build_domain"
+        | "commit_to_main_trace_segment"
+        | "build_aux_trace"
+        | "commit_to_aux_trace_segment" => Some(Phase::BuildingTrace),
+        "evaluate_constraints"
+        | "commit_to_constraint_evaluations"
+        | "build_deep_composition_poly"
+        | "evaluate_deep_composition_poly" => Some(Phase::Committing),
+        "compute_fri_layers" | "determine_query_positions" | "build_proof_object" => {
+            Some(Phase::Fri)
+        }
+        _ => None,
+    }
+}
+
+/// Reads the `trace_length` field of the `build_domain` span.
+#[derive(Default)]
+struct TraceLength(Option<u64>);
+
+impl Visit for TraceLength {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "trace_length" {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+impl Subscriber for PhaseSubscriber {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.is_span()
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        if self.progress.is_cancelled() {
+            // `resume_unwind` skips the panic hook, so cancelling does not log a panic
+            panic::resume_unwind(Box::new(Cancelled));
+        }
+        if let Some(phase) = span_phase(span.metadata().name()) {
+            let mut trace_length = TraceLength::default();
+            span.record(&mut trace_length);
+            if let Some(cycles) = trace_length.0 {
+                self.progress.cycles(cycles);
+            }
+            self.progress.phase(phase);
+        }
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, _event: &Event<'_>) {}
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
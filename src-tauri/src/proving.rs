@@ -0,0 +1,169 @@
+use miden_vm::{ExecutionProof, FieldExtension, HashFunction, ProvingOptions};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofHashFunction {
+    Blake3_192,
+    Blake3_256,
+    Rpo,
+    Rpx,
+}
+
+impl From<HashFunction> for ProofHashFunction {
+    fn from(hash_fn: HashFunction) -> Self {
+        match hash_fn {
+            HashFunction::Blake3_192 => ProofHashFunction::Blake3_192,
+            HashFunction::Blake3_256 => ProofHashFunction::Blake3_256,
+            HashFunction::Rpo256 => ProofHashFunction::Rpo,
+            HashFunction::Rpx256 => ProofHashFunction::Rpx,
+        }
+    }
+}
+
+/// Proving options requested by the client; missing fields fall back to the VM defaults.
+#[derive(Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct ProvingOptionsRequest {
+    /// Target security level in bits, either 96 (the default) or 128.
+    pub security_level: Option<u32>,
+    /// Hash function used by the prover; defaults to the BLAKE3 variant matching the security
+    /// level, which is also the only BLAKE3 variant allowed at that level.
+    pub hash_function: Option<ProofHashFunction>,
+}
+
+/// The options a proof was actually generated with, read back from the proof itself.
+#[derive(Serialize, Clone)]
+pub struct ProvingOptionsUsed {
+    pub security_level: u32,
+    pub hash_function: ProofHashFunction,
+    pub num_queries: usize,
+    pub blowup_factor: usize,
+    pub grinding_factor: u32,
+    pub field_extension: String,
+    /// Conjectured security of the generated proof in bits.
+    pub conjectured_security: u32,
+}
+
+impl ProvingOptionsRequest {
+    pub fn security_level(&self) -> u32 {
+        self.security_level.unwrap_or(96)
+    }
+
+    /// Validates the request and maps it onto one of the VM's proving presets.
+    pub fn resolve(&self) -> Result<ProvingOptions, String> {
+        let options = match (self.security_level(), self.hash_function) {
+            (96, None | Some(ProofHashFunction::Blake3_192)) => {
+                ProvingOptions::with_96_bit_security(false)
+            }
+            (96, Some(ProofHashFunction::Rpo)) => ProvingOptions::with_96_bit_security(true),
+            (96, Some(ProofHashFunction::Rpx)) => ProvingOptions::with_96_bit_security_rpx(),
+            // The verifier only accepts BLAKE3 proofs made with the matching preset
+            (96, Some(ProofHashFunction::Blake3_256)) => {
+                return Err(
+                    "Invalid proving options: blake3_256 is only supported at 128-bit security"
+                        .to_string(),
+                )
+            }
+            (128, Some(ProofHashFunction::Blake3_192)) => {
+                return Err(
+                    "Invalid proving options: blake3_192 only provides 96-bit collision \
+                     resistance and cannot be used for 128-bit security"
+                        .to_string(),
+                )
+            }
+            (128, None | Some(ProofHashFunction::Blake3_256)) => {
+                ProvingOptions::with_128_bit_security(false)
+            }
+            (128, Some(ProofHashFunction::Rpo)) => ProvingOptions::with_128_bit_security(true),
+            (128, Some(ProofHashFunction::Rpx)) => ProvingOptions::with_128_bit_security_rpx(),
+            (bits, _) => {
+                return Err(format!(
+                    "Invalid proving options: security level must be 96 or 128 bits, got {bits}"
+                ))
+            }
+        };
+        Ok(options)
+    }
+}
+
+impl ProvingOptionsUsed {
+    pub fn new(security_level: u32, proof: &ExecutionProof) -> Self {
+        let options = proof.stark_proof().options();
+        ProvingOptionsUsed {
+            security_level,
+            hash_function: proof.hash_fn().into(),
+            num_queries: options.num_queries(),
+            blowup_factor: options.blowup_factor(),
+            grinding_factor: options.grinding_factor(),
+            field_extension: match options.field_extension() {
+                FieldExtension::None => "none",
+                FieldExtension::Quadratic => "quadratic",
+                FieldExtension::Cubic => "cubic",
+            }
+            .to_string(),
+            conjectured_security: proof.security_level(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(
+        security_level: Option<u32>,
+        hash_function: Option<ProofHashFunction>,
+    ) -> Result<ProvingOptions, String> {
+        ProvingOptionsRequest {
+            security_level,
+            hash_function,
+        }
+        .resolve()
+    }
+
+    #[test]
+    fn resolves_presets() {
+        use ProofHashFunction::*;
+
+        assert_eq!(
+            resolve(None, None),
+            Ok(ProvingOptions::with_96_bit_security(false))
+        );
+        assert_eq!(
+            resolve(Some(96), Some(Blake3_192)),
+            Ok(ProvingOptions::with_96_bit_security(false))
+        );
+        assert_eq!(
+            resolve(None, Some(Rpo)),
+            Ok(ProvingOptions::with_96_bit_security(true))
+        );
+        assert_eq!(
+            resolve(Some(96), Some(Rpx)),
+            Ok(ProvingOptions::with_96_bit_security_rpx())
+        );
+        assert_eq!(
+            resolve(Some(128), None),
+            Ok(ProvingOptions::with_128_bit_security(false))
+        );
+        assert_eq!(
+            resolve(Some(128), Some(Blake3_256)),
+            Ok(ProvingOptions::with_128_bit_security(false))
+        );
+        assert_eq!(
+            resolve(Some(128), Some(Rpo)),
+            Ok(ProvingOptions::with_128_bit_security(true))
+        );
+        assert_eq!(
+            resolve(Some(128), Some(Rpx)),
+            Ok(ProvingOptions::with_128_bit_security_rpx())
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_options() {
+        assert!(resolve(Some(96), Some(ProofHashFunction::Blake3_256)).is_err());
+        assert!(resolve(Some(128), Some(ProofHashFunction::Blake3_192)).is_err());
+        assert!(resolve(Some(100), None).is_err());
+    }
+}
@@ -0,0 +1,524 @@
+use crate::playground::{ModuleSource, PLAYGROUND};
+use miden_vm::assembly::DefaultSourceManager;
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable overriding the directory saved programs are stored in.
+const DATA_DIR_ENV: &str = "MASM_PLAYGROUND_DATA_DIR";
+/// Where the web server stores programs, relative to its working directory.
+const DEFAULT_DATA_DIR: &str = "data";
+/// Holds the ID the next saved program gets, in the programs directory.
+const NEXT_ID_FILE: &str = "next_id";
+
+/// The data directory of the desktop app; see [set_data_dir].
+static APP_DATA_DIR: OnceCell<PathBuf> = OnceCell::new();
+
+/// Serializes every read-modify-write of the program files.
+static STORE_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+#[derive(Deserialize)]
+pub struct SaveProgramRequest {
+    pub name: String,
+    pub program: String,
+    pub inputs: Option<Value>,
+    #[serde(default)]
+    pub modules: Vec<ModuleSource>,
+}
+
+#[derive(Deserialize)]
+pub struct RenameProgramRequest {
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProgramVersion {
+    pub version: u32,
+    pub program: String,
+    pub inputs: Option<Value>,
+    pub modules: Vec<ModuleSource>,
+    /// MAST root of the program, or `None` if this version did not assemble.
+    pub mast_root: Option<String>,
+    /// Whether the MAST root differs from the previous version, i.e. the code changed semantically
+    /// rather than just in formatting or comments.
+    pub semantic_change: bool,
+    pub saved_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct StoredProgram {
+    pub id: u64,
+    pub name: String,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub versions: Vec<ProgramVersion>,
+}
+
+#[derive(Serialize)]
+pub struct ProgramSummary {
+    pub id: u64,
+    pub name: String,
+    pub latest_version: u32,
+    pub mast_root: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+#[derive(Serialize)]
+pub struct StorageResult {
+    pub success: bool,
+    pub program: Option<StoredProgram>,
+    pub version: Option<ProgramVersion>,
+    pub programs: Option<Vec<ProgramSummary>>,
+    pub error: Option<String>,
+    /// Problems that did not fail the operation, such as program files that could not be listed.
+    pub warnings: Vec<String>,
+}
+
+impl StorageResult {
+    fn empty() -> Self {
+        StorageResult {
+            success: true,
+            program: None,
+            version: None,
+            programs: None,
+            error: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn from_result<T>(result: Result<T, String>, fill: impl FnOnce(&mut Self, T)) -> Self {
+        match result {
+            Ok(value) => {
+                let mut storage_result = StorageResult::empty();
+                fill(&mut storage_result, value);
+                storage_result
+            }
+            Err(e) => StorageResult {
+                success: false,
+                error: Some(e),
+                ..StorageResult::empty()
+            },
+        }
+    }
+}
+
+/// Stores programs in `dir` rather than the default directory, unless `MASM_PLAYGROUND_DATA_DIR`
+/// is set. The desktop app passes its app data directory, since an installed app has no
+/// meaningful working directory. Only the first call has an effect.
+pub fn set_data_dir(dir: PathBuf) {
+    let _ = APP_DATA_DIR.set(dir);
+}
+
+fn data_dir() -> PathBuf {
+    std::env::var_os(DATA_DIR_ENV)
+        .map(PathBuf::from)
+        .or_else(|| APP_DATA_DIR.get().cloned())
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_DATA_DIR))
+}
+
+fn programs_dir() -> Result<PathBuf, String> {
+    let dir = data_dir().join("programs");
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create data directory {}: {e}", dir.display()))?;
+    Ok(dir)
+}
+
+fn program_path(id: u64) -> Result<PathBuf, String> {
+    Ok(programs_dir()?.join(format!("{id}.json")))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn read_program(id: u64) -> Result<StoredProgram, String> {
+    let path = program_path(id)?;
+    let contents = fs::read_to_string(&path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => format!("Program {id} not found"),
+        _ => format!("Failed to read program {id}: {e}"),
+    })?;
+    serde_json::from_str(&contents).map_err(|e| format!("Program {id} is corrupted: {e}"))
+}
+
+fn write_program(program: &StoredProgram) -> Result<(), String> {
+    let path = program_path(program.id)?;
+    let contents = serde_json::to_string_pretty(program).unwrap();
+
+    // Write to a temporary file first so a crash never leaves a half-written program behind
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, contents)
+        .and_then(|_| fs::rename(&tmp_path, &path))
+        .map_err(|e| format!("Failed to save program {}: {e}", program.id))
+}
+
+/// The IDs of the stored programs, in ascending order.
+fn program_ids() -> Result<Vec<u64>, String> {
+    let dir = programs_dir()?;
+    let entries =
+        fs::read_dir(&dir).map_err(|e| format!("Failed to list {}: {e}", dir.display()))?;
+
+    let mut ids: Vec<u64> = entries
+        .flatten()
+        .filter_map(|entry| {
+            entry
+                .path()
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(".json"))
+                .and_then(|id| id.parse().ok())
+        })
+        .collect();
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+/// Reads every stored program. A file that cannot be read or parsed does not hide the others: it
+/// is skipped, and the error is returned alongside the programs that could be read.
+fn read_all_programs() -> Result<(Vec<StoredProgram>, Vec<String>), String> {
+    let mut programs = Vec::new();
+    let mut errors = Vec::new();
+    for id in program_ids()? {
+        match read_program(id) {
+            Ok(program) => programs.push(program),
+            Err(e) => errors.push(e),
+        }
+    }
+    Ok((programs, errors))
+}
+
+/// Takes the next program ID from a counter kept next to the programs, so the ID of a deleted
+/// program is never handed out again. Without a counter, e.g. in a data directory from before it
+/// existed, counting starts after the highest ID in use.
+fn next_program_id() -> Result<u64, String> {
+    let path = programs_dir()?.join(NEXT_ID_FILE);
+    let id = match fs::read_to_string(&path) {
+        Ok(contents) => contents
+            .trim()
+            .parse()
+            .map_err(|e| format!("Program ID counter {} is corrupted: {e}", path.display()))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            program_ids()?.last().map_or(1, |id| id + 1)
+        }
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, (id + 1).to_string())
+        .and_then(|_| fs::rename(&tmp_path, &path))
+        .map_err(|e| format!("Failed to update {}: {e}", path.display()))?;
+    Ok(id)
+}
+
+fn mast_root(program: &str, modules: &[ModuleSource]) -> Option<String> {
+    let source_manager = Arc::new(DefaultSourceManager::default());
+    let assembler = PLAYGROUND.assembler(&source_manager, modules).ok()?;
+    let program = assembler.assemble_program(program).ok()?;
+    Some(program.hash().to_string())
+}
+
+fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Program name must not be empty".to_string());
+    }
+    Ok(name.to_string())
+}
+
+/// Appends a version with the given MAST root to `stored`, unless it is identical to the latest
+/// one. The root is computed by the caller, so programs are not assembled under [STORE_LOCK].
+fn add_version(stored: &mut StoredProgram, request: SaveProgramRequest, mast_root: Option<String>) {
+    if let Some(latest) = stored.versions.last() {
+        if latest.program == request.program
+            && latest.inputs == request.inputs
+            && latest.modules == request.modules
+        {
+            return;
+        }
+    }
+
+    let semantic_change = match stored.versions.last() {
+        Some(latest) => latest.mast_root != mast_root,
+        None => true,
+    };
+
+    stored.updated_at = now();
+    stored.versions.push(ProgramVersion {
+        version: stored.versions.len() as u32 + 1,
+        program: request.program,
+        inputs: request.inputs,
+        modules: request.modules,
+        mast_root,
+        semantic_change,
+        saved_at: stored.updated_at,
+    });
+}
+
+#[tauri::command]
+pub fn save_program(
+    id: Option<u64>,
+    name: String,
+    program: String,
+    inputs_json: Option<String>,
+    modules: Option<Vec<ModuleSource>>,
+) -> Result<String, String> {
+    let inputs = match inputs_json {
+        Some(inputs) => {
+            Some(serde_json::from_str(&inputs).map_err(|e| format!("Invalid inputs JSON: {e}"))?)
+        }
+        None => None,
+    };
+    let request = SaveProgramRequest {
+        name,
+        program,
+        inputs,
+        modules: modules.unwrap_or_default(),
+    };
+
+    let result = match id {
+        Some(id) => update_program_impl(id, request),
+        None => create_program_impl(request),
+    };
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+#[tauri::command]
+pub fn list_programs() -> Result<String, String> {
+    Ok(serde_json::to_string(&list_programs_impl()).unwrap())
+}
+
+#[tauri::command]
+pub fn load_program(id: u64, version: Option<u32>) -> Result<String, String> {
+    let result = match version {
+        Some(version) => load_program_version_impl(id, version),
+        None => load_program_impl(id),
+    };
+    Ok(serde_json::to_string(&result).unwrap())
+}
+
+#[tauri::command]
+pub fn rename_program(id: u64, name: String) -> Result<String, String> {
+    Ok(serde_json::to_string(&rename_program_impl(id, &name)).unwrap())
+}
+
+#[tauri::command]
+pub fn delete_program(id: u64) -> Result<String, String> {
+    Ok(serde_json::to_string(&delete_program_impl(id)).unwrap())
+}
+
+pub fn create_program_impl(request: SaveProgramRequest) -> StorageResult {
+    let mast_root = mast_root(&request.program, &request.modules);
+    let _lock = STORE_LOCK.lock().unwrap();
+
+    let result = (|| {
+        let name = validate_name(&request.name)?;
+        let id = next_program_id()?;
+
+        let mut stored = StoredProgram {
+            id,
+            name,
+            created_at: 0,
+            updated_at: 0,
+            versions: Vec::new(),
+        };
+        add_version(&mut stored, request, mast_root);
+        stored.created_at = stored.updated_at;
+        write_program(&stored)?;
+        Ok(stored)
+    })();
+
+    StorageResult::from_result(result, |r, stored| r.program = Some(stored))
+}
+
+/// Saves a new version of an existing program, also renaming it if the name changed.
+pub fn update_program_impl(id: u64, request: SaveProgramRequest) -> StorageResult {
+    let mast_root = mast_root(&request.program, &request.modules);
+    let _lock = STORE_LOCK.lock().unwrap();
+
+    let result = (|| {
+        let mut stored = read_program(id)?;
+        stored.name = validate_name(&request.name)?;
+        add_version(&mut stored, request, mast_root);
+        write_program(&stored)?;
+        Ok(stored)
+    })();
+
+    StorageResult::from_result(result, |r, stored| r.program = Some(stored))
+}
+
+pub fn list_programs_impl() -> StorageResult {
+    let _lock = STORE_LOCK.lock().unwrap();
+
+    let result = read_all_programs().map(|(programs, errors)| {
+        let summaries = programs
+            .into_iter()
+            .map(|program| {
+                let latest = program.versions.last();
+                ProgramSummary {
+                    id: program.id,
+                    name: program.name,
+                    latest_version: latest.map(|v| v.version).unwrap_or(0),
+                    mast_root: latest.and_then(|v| v.mast_root.clone()),
+                    created_at: program.created_at,
+                    updated_at: program.updated_at,
+                }
+            })
+            .collect();
+        (summaries, errors)
+    });
+
+    StorageResult::from_result(result, |r, (programs, errors)| {
+        r.programs = Some(programs);
+        r.warnings = errors;
+    })
+}
+
+pub fn load_program_impl(id: u64) -> StorageResult {
+    let _lock = STORE_LOCK.lock().unwrap();
+    StorageResult::from_result(read_program(id), |r, stored| r.program = Some(stored))
+}
+
+pub fn load_program_version_impl(id: u64, version: u32) -> StorageResult {
+    let _lock = STORE_LOCK.lock().unwrap();
+
+    let result = read_program(id).and_then(|stored| {
+        stored
+            .versions
+            .into_iter()
+            .find(|v| v.version == version)
+            .ok_or_else(|| format!("Program {id} has no version {version}"))
+    });
+
+    StorageResult::from_result(result, |r, version| r.version = Some(version))
+}
+
+pub fn rename_program_impl(id: u64, name: &str) -> StorageResult {
+    let _lock = STORE_LOCK.lock().unwrap();
+
+    let result = (|| {
+        let mut stored = read_program(id)?;
+        stored.name = validate_name(name)?;
+        stored.updated_at = now();
+        write_program(&stored)?;
+        Ok(stored)
+    })();
+
+    StorageResult::from_result(result, |r, stored| r.program = Some(stored))
+}
+
+pub fn delete_program_impl(id: u64) -> StorageResult {
+    let _lock = STORE_LOCK.lock().unwrap();
+
+    let result = program_path(id).and_then(|path| {
+        fs::remove_file(path).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => format!("Program {id} not found"),
+            _ => format!("Failed to delete program {id}: {e}"),
+        })
+    });
+
+    StorageResult::from_result(result, |_, _| {})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Points every test at the same fresh directory; tests only look at the programs they create.
+    fn use_test_dir() {
+        let dir = std::env::temp_dir().join(format!("masm-playground-test-{}", std::process::id()));
+        set_data_dir(dir);
+    }
+
+    fn request(program: &str) -> SaveProgramRequest {
+        SaveProgramRequest {
+            name: "test".to_string(),
+            program: program.to_string(),
+            inputs: None,
+            modules: Vec::new(),
+        }
+    }
+
+    fn create(program: &str) -> StoredProgram {
+        let result = create_program_impl(request(program));
+        assert!(result.success, "{:?}", result.error);
+        result.program.unwrap()
+    }
+
+    fn update(id: u64, program: &str) -> StoredProgram {
+        let result = update_program_impl(id, request(program));
+        assert!(result.success, "{:?}", result.error);
+        result.program.unwrap()
+    }
+
+    #[test]
+    fn versions_record_semantic_changes() {
+        use_test_dir();
+        let id = create("begin push.1 end").id;
+        update(id, "begin\n    # same code, new comment\n    push.1\nend");
+        update(id, "begin\n    # same code, new comment\n    push.1\nend");
+        update(id, "begin push.2 end");
+        let stored = update(id, "begin push.2");
+
+        let versions = &stored.versions;
+        assert_eq!(
+            versions.iter().map(|v| v.version).collect::<Vec<_>>(),
+            [1, 2, 3, 4],
+            "saving an identical program adds no version"
+        );
+        assert!(versions[0].mast_root.is_some());
+        assert_eq!(versions[1].mast_root, versions[0].mast_root);
+        assert_ne!(versions[2].mast_root, versions[1].mast_root);
+        assert_eq!(versions[3].mast_root, None);
+        assert_eq!(
+            versions
+                .iter()
+                .map(|v| v.semantic_change)
+                .collect::<Vec<_>>(),
+            [true, false, true, true]
+        );
+
+        let version = load_program_version_impl(id, 2).version.unwrap();
+        assert!(version.program.contains("new comment"));
+        assert!(!load_program_version_impl(id, 5).success);
+    }
+
+    #[test]
+    fn ids_of_deleted_programs_are_not_reused() {
+        use_test_dir();
+        let first = create("begin push.1 end").id;
+        let second = create("begin push.1 end").id;
+        assert!(second > first);
+
+        assert!(delete_program_impl(second).success);
+        assert!(!load_program_impl(second).success);
+        assert!(create("begin push.1 end").id > second);
+    }
+
+    #[test]
+    fn corrupt_files_are_skipped_when_listing() {
+        use_test_dir();
+        let id = create("begin push.1 end").id;
+        let corrupt = 999_999_999;
+        fs::write(program_path(corrupt).unwrap(), "not json").unwrap();
+
+        let result = list_programs_impl();
+        assert!(result.success, "{:?}", result.error);
+        let ids: Vec<u64> = result.programs.unwrap().iter().map(|p| p.id).collect();
+        assert!(ids.contains(&id));
+        assert!(!ids.contains(&corrupt));
+        assert!(result
+            .warnings
+            .iter()
+            .any(|warning| warning.contains(&corrupt.to_string())));
+
+        fs::remove_file(program_path(corrupt).unwrap()).unwrap();
+    }
+}
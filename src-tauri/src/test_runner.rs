@@ -0,0 +1,623 @@
+use crate::memory::MemoryQuery;
+use crate::outputs::parse_formatted_felt;
+use crate::playground::{
+    ErrorKind, ExecutionRequest, ExecutionResult, ExecutionSettings, ModuleSource, Playground,
+};
+use crate::progress::Progress;
+use miden_core::Felt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+/// A set of test cases sharing the same library modules.
+#[derive(Deserialize, Clone)]
+pub struct TestSuite {
+    #[serde(default = "default_suite_name")]
+    pub name: String,
+    /// Library modules available to every case, e.g. the module defining the procedures under
+    /// test.
+    #[serde(default)]
+    pub modules: Vec<ModuleSource>,
+    pub cases: Vec<TestCase>,
+}
+
+fn default_suite_name() -> String {
+    "masm".to_string()
+}
+
+#[derive(Deserialize, Clone)]
+pub struct TestCase {
+    pub name: String,
+    /// Program to run; exactly one of `program` and `procedure` must be set.
+    pub program: Option<String>,
+    /// Fully qualified procedure to call, such as `myproj::math::square`; the case runs a program
+    /// that does nothing but `exec` it.
+    pub procedure: Option<String>,
+    /// Inputs in the same format as execution requests, including advice.
+    pub inputs: Option<Value>,
+    /// The expected top of the output stack, in any output format.
+    #[serde(default)]
+    pub expected_stack: Vec<String>,
+    #[serde(default)]
+    pub expected_memory: Vec<ExpectedMemory>,
+    /// Text the error must contain for the case to pass; an empty string accepts any execution
+    /// error, but not an assembly or input error.
+    pub expected_error: Option<String>,
+    #[serde(default)]
+    pub settings: ExecutionSettings,
+}
+
+/// The value one memory element should hold when the program finishes.
+#[derive(Deserialize, Clone)]
+pub struct ExpectedMemory {
+    #[serde(default)]
+    pub context: u32,
+    pub address: u32,
+    pub value: String,
+}
+
+#[derive(Deserialize)]
+pub struct TestRequest {
+    #[serde(flatten)]
+    pub suite: TestSuite,
+    #[serde(default)]
+    pub format: TestReportFormat,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    /// The case itself is invalid, e.g. it names neither a program nor a procedure.
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TestReportFormat {
+    #[default]
+    Json,
+    Junit,
+    Tap,
+}
+
+/// One position of the expected stack next to what the program actually left there.
+#[derive(Serialize)]
+pub struct StackDiffEntry {
+    pub position: usize,
+    pub expected: String,
+    pub actual: Option<String>,
+    pub matches: bool,
+}
+
+#[derive(Serialize)]
+pub struct MemoryDiffEntry {
+    pub context: u32,
+    pub address: u32,
+    pub expected: String,
+    pub actual: String,
+    pub matches: bool,
+}
+
+#[derive(Serialize)]
+pub struct TestCaseResult {
+    pub name: String,
+    pub status: TestStatus,
+    /// Why the case failed.
+    pub message: Option<String>,
+    pub error: Option<String>,
+    pub error_kind: Option<ErrorKind>,
+    pub cycles: Option<u32>,
+    /// Filled in when the output stack did not match.
+    pub stack_diff: Vec<StackDiffEntry>,
+    /// Filled in when memory did not match.
+    pub memory_diff: Vec<MemoryDiffEntry>,
+    pub time_ms: f64,
+}
+
+#[derive(Serialize)]
+pub struct TestReport {
+    pub success: bool,
+    pub name: String,
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub errors: usize,
+    pub cases: Vec<TestCaseResult>,
+    pub time_ms: f64,
+}
+
+/// Runs every case of the suite, spreading them over one thread per CPU. Servers run the whole
+/// suite as one job, so a suite takes a single slot of the job queue however many cases it has.
+/// Once `progress` is cancelled no further cases are started, leaving them out of the report.
+pub fn run_test_suite(
+    playground: &Playground,
+    suite: &TestSuite,
+    progress: &Progress,
+) -> TestReport {
+    let start = Instant::now();
+    let next_case = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(suite.cases.len()));
+    let threads = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(suite.cases.len());
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                if progress.is_cancelled() {
+                    break;
+                }
+                let index = next_case.fetch_add(1, Ordering::Relaxed);
+                let Some(case) = suite.cases.get(index) else {
+                    break;
+                };
+                let result = run_test_case(playground, &suite.modules, case);
+                results.lock().unwrap().push((index, result));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    let cases: Vec<TestCaseResult> = results.into_iter().map(|(_, result)| result).collect();
+
+    let count = |status| cases.iter().filter(|case| case.status == status).count();
+    let (passed, failed, errors) = (
+        count(TestStatus::Passed),
+        count(TestStatus::Failed),
+        count(TestStatus::Error),
+    );
+    TestReport {
+        success: failed == 0 && errors == 0,
+        name: suite.name.clone(),
+        total: cases.len(),
+        passed,
+        failed,
+        errors,
+        cases,
+        time_ms: start.elapsed().as_millis() as f64,
+    }
+}
+
+fn run_test_case(
+    playground: &Playground,
+    modules: &[ModuleSource],
+    case: &TestCase,
+) -> TestCaseResult {
+    let start = Instant::now();
+    let mut result = TestCaseResult {
+        name: case.name.clone(),
+        status: TestStatus::Passed,
+        message: None,
+        error: None,
+        error_kind: None,
+        cycles: None,
+        stack_diff: Vec::new(),
+        memory_diff: Vec::new(),
+        time_ms: 0.0,
+    };
+
+    match check_case(playground, modules, case, &mut result) {
+        Ok(None) => {}
+        Ok(Some(message)) => {
+            result.status = TestStatus::Failed;
+            result.message = Some(message);
+        }
+        Err(message) => {
+            result.status = TestStatus::Error;
+            result.message = Some(message);
+        }
+    }
+    result.time_ms = start.elapsed().as_millis() as f64;
+    result
+}
+
+/// Runs a case and compares it against its expectations, returning why it failed, if it did.
+/// An `Err` means the case could not be run at all.
+fn check_case(
+    playground: &Playground,
+    modules: &[ModuleSource],
+    case: &TestCase,
+    result: &mut TestCaseResult,
+) -> Result<Option<String>, String> {
+    let expected_stack = case
+        .expected_stack
+        .iter()
+        .map(|value| parse_formatted_felt(value))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut settings = case.settings.clone();
+    // Only the final value of the expected cells matters, so the access log is left out
+    let addresses = case.expected_memory.iter().map(|cell| cell.address);
+    if let (Some(start), Some(end)) = (addresses.clone().min(), addresses.max()) {
+        settings.memory = Some(MemoryQuery {
+            start_address: Some(start),
+            end_address: end.checked_add(1),
+            end_cycle: Some(0),
+            unpaged: true,
+            ..Default::default()
+        });
+    }
+    let execution = playground.execute(&ExecutionRequest {
+        program: case_program(case)?,
+        inputs: case.inputs.clone(),
+        modules: modules.to_vec(),
+        settings,
+        ..Default::default()
+    });
+    result.error = execution.error.clone();
+    result.error_kind = execution.error_kind;
+    result.cycles = execution.cycles;
+
+    if let Some(expected_error) = &case.expected_error {
+        return Ok(match &execution.error {
+            None if expected_error.is_empty() => {
+                Some("Expected the program to fail, but it succeeded".to_string())
+            }
+            None => Some(format!(
+                "Expected the program to fail with '{expected_error}', but it succeeded"
+            )),
+            // An empty expectation accepts any failure of the program itself, but not a program
+            // that could not be built or run
+            Some(error)
+                if expected_error.is_empty()
+                    && !matches!(
+                        execution.error_kind,
+                        Some(ErrorKind::Execution | ErrorKind::CycleLimitExceeded)
+                    ) =>
+            {
+                Some(format!(
+                    "Expected the program to fail during execution, but it failed with: {error}"
+                ))
+            }
+            Some(error) if !error.contains(expected_error.as_str()) => Some(format!(
+                "Expected the program to fail with '{expected_error}', but it failed with: {error}"
+            )),
+            Some(_) => None,
+        });
+    }
+
+    // A program leaving too many elements still reports its stack, which is worth diffing
+    let actual_stack = execution.stack_outputs.clone().unwrap_or_default();
+    if !expected_stack.is_empty() {
+        let diff = stack_diff(&case.expected_stack, &expected_stack, &actual_stack);
+        if diff.iter().any(|entry| !entry.matches) {
+            result.stack_diff = diff;
+        }
+    }
+    if !execution.success {
+        return Ok(Some(execution.error.unwrap_or_default()));
+    }
+    if !result.stack_diff.is_empty() {
+        return Ok(Some("Output stack does not match".to_string()));
+    }
+
+    let diff = memory_diff(&case.expected_memory, &execution)?;
+    if diff.iter().any(|entry| !entry.matches) {
+        result.memory_diff = diff;
+        return Ok(Some("Memory does not match".to_string()));
+    }
+    Ok(None)
+}
+
+fn case_program(case: &TestCase) -> Result<String, String> {
+    match (&case.program, &case.procedure) {
+        (Some(program), None) => Ok(program.clone()),
+        (None, Some(procedure)) => {
+            let (module, name) = procedure.rsplit_once("::").ok_or_else(|| {
+                format!("Procedure '{procedure}' must be qualified with its module path")
+            })?;
+            let alias = module.rsplit("::").next().unwrap_or(module);
+            Ok(format!(
+                "use.{module}\n\nbegin\n    exec.{alias}::{name}\nend\n"
+            ))
+        }
+        (Some(_), Some(_)) => Err("A test case cannot set both program and procedure".to_string()),
+        (None, None) => Err("A test case needs a program or a procedure".to_string()),
+    }
+}
+
+fn stack_diff(
+    expected_text: &[String],
+    expected: &[Felt],
+    actual: &[String],
+) -> Vec<StackDiffEntry> {
+    expected_text
+        .iter()
+        .zip(expected)
+        .enumerate()
+        .map(|(position, (text, &value))| {
+            let actual = actual.get(position);
+            StackDiffEntry {
+                position,
+                expected: text.clone(),
+                actual: actual.cloned(),
+                matches: actual.and_then(|a| parse_formatted_felt(a).ok()) == Some(value),
+            }
+        })
+        .collect()
+}
+
+fn memory_diff(
+    expected: &[ExpectedMemory],
+    execution: &ExecutionResult,
+) -> Result<Vec<MemoryDiffEntry>, String> {
+    let cells: HashMap<(u32, u32), &str> = execution
+        .memory
+        .iter()
+        .flat_map(|report| &report.cells)
+        .map(|cell| ((cell.context, cell.address), cell.value.as_str()))
+        .collect();
+
+    expected
+        .iter()
+        .map(|cell| {
+            let expected_value = parse_formatted_felt(&cell.value)?;
+            // Memory that was never written reads as zero
+            let actual = cells
+                .get(&(cell.context, cell.address))
+                .copied()
+                .unwrap_or("0");
+            Ok(MemoryDiffEntry {
+                context: cell.context,
+                address: cell.address,
+                expected: cell.value.clone(),
+                actual: actual.to_string(),
+                matches: parse_formatted_felt(actual).ok() == Some(expected_value),
+            })
+        })
+        .collect()
+}
+
+impl TestCaseResult {
+    /// The failure message followed by the stack and memory diffs, one line each.
+    pub fn details(&self) -> String {
+        let mut details = self.message.clone().unwrap_or_default();
+        if !self.stack_diff.is_empty() {
+            let column = |values: Vec<&str>| values.join(", ");
+            let _ = write!(
+                details,
+                "\nexpected stack: [{}]\nactual stack:   [{}]",
+                column(
+                    self.stack_diff
+                        .iter()
+                        .map(|e| e.expected.as_str())
+                        .collect()
+                ),
+                column(
+                    self.stack_diff
+                        .iter()
+                        .map(|e| e.actual.as_deref().unwrap_or("-"))
+                        .collect()
+                ),
+            );
+            for entry in self.stack_diff.iter().filter(|entry| !entry.matches) {
+                let _ = write!(
+                    details,
+                    "\n  [{}] expected {}, got {}",
+                    entry.position,
+                    entry.expected,
+                    entry.actual.as_deref().unwrap_or("nothing")
+                );
+            }
+        }
+        for entry in self.memory_diff.iter().filter(|entry| !entry.matches) {
+            let _ = write!(
+                details,
+                "\n  memory[{}:{}] expected {}, got {}",
+                entry.context, entry.address, entry.expected, entry.actual
+            );
+        }
+        details
+    }
+}
+
+impl TestReport {
+    pub fn render(&self, format: TestReportFormat) -> String {
+        match format {
+            TestReportFormat::Json => serde_json::to_string_pretty(self).unwrap(),
+            TestReportFormat::Junit => self.to_junit(),
+            TestReportFormat::Tap => self.to_tap(),
+        }
+    }
+
+    pub fn to_junit(&self) -> String {
+        let seconds = |ms: f64| ms / 1000.0;
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">",
+            self.total,
+            self.failed,
+            self.errors,
+            seconds(self.time_ms)
+        );
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">",
+            xml_escape(&self.name),
+            self.total,
+            self.failed,
+            self.errors,
+            seconds(self.time_ms)
+        );
+        for case in &self.cases {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                xml_escape(&case.name),
+                xml_escape(&self.name),
+                seconds(case.time_ms)
+            );
+            let element = match case.status {
+                TestStatus::Passed => {
+                    xml.push_str("/>\n");
+                    continue;
+                }
+                TestStatus::Failed => "failure",
+                TestStatus::Error => "error",
+            };
+            let _ = writeln!(
+                xml,
+                ">\n      <{element} message=\"{}\">{}</{element}>\n    </testcase>",
+                xml_escape(case.message.as_deref().unwrap_or_default()),
+                xml_escape(&case.details())
+            );
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+
+    pub fn to_tap(&self) -> String {
+        let mut tap = format!("TAP version 13\n1..{}\n", self.total);
+        for (number, case) in (1..).zip(&self.cases) {
+            let ok = if case.status == TestStatus::Passed {
+                "ok"
+            } else {
+                "not ok"
+            };
+            let _ = writeln!(tap, "{ok} {number} - {}", case.name);
+            if case.status != TestStatus::Passed {
+                let _ = writeln!(tap, "  ---");
+                let _ = writeln!(
+                    tap,
+                    "  status: {}",
+                    serde_json::to_string(&case.status).unwrap()
+                );
+                let _ = writeln!(tap, "  details: |");
+                for line in case.details().lines() {
+                    let _ = writeln!(tap, "    {line}");
+                }
+                let _ = writeln!(tap, "  ...");
+            }
+        }
+        let _ = writeln!(
+            tap,
+            "# {} passed, {} failed, {} errors",
+            self.passed, self.failed, self.errors
+        );
+        tap
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::playground::PLAYGROUND;
+    use serde_json::json;
+
+    fn run(cases: Value) -> TestReport {
+        let suite = serde_json::from_value(json!({ "name": "arith", "cases": cases })).unwrap();
+        run_test_suite(&PLAYGROUND, &suite, &Progress::default())
+    }
+
+    fn sample_report() -> TestReport {
+        run(json!([
+            { "name": "adds", "program": "begin push.1 push.2 add swap drop end", "expected_stack": ["3"] },
+            { "name": "adds wrong", "program": "begin push.1 push.2 add swap drop end", "expected_stack": ["4"] },
+            {
+                "name": "stores",
+                "program": "begin push.7 mem_store.100 end",
+                "expected_memory": [{ "address": 100, "value": "8" }]
+            },
+            { "name": "asserts", "program": "begin push.0 assert end", "expected_error": "" },
+            { "name": "empty" },
+        ]))
+    }
+
+    #[test]
+    fn cases_are_reported_in_order_with_their_diffs() {
+        let report = sample_report();
+        assert!(!report.success);
+        assert_eq!(
+            (report.total, report.passed, report.failed, report.errors),
+            (5, 2, 2, 1)
+        );
+        let statuses: Vec<_> = report.cases.iter().map(|case| case.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                TestStatus::Passed,
+                TestStatus::Failed,
+                TestStatus::Failed,
+                TestStatus::Passed,
+                TestStatus::Error
+            ]
+        );
+
+        let stack = &report.cases[1].stack_diff;
+        assert_eq!(stack.len(), 1);
+        assert_eq!(
+            (stack[0].expected.as_str(), stack[0].actual.as_deref()),
+            ("4", Some("3"))
+        );
+        assert!(!stack[0].matches);
+
+        let memory = &report.cases[2].memory_diff;
+        assert_eq!(memory.len(), 1);
+        assert_eq!(
+            (
+                memory[0].address,
+                memory[0].expected.as_str(),
+                memory[0].actual.as_str()
+            ),
+            (100, "8", "7")
+        );
+        assert!(report.cases[0].stack_diff.is_empty());
+    }
+
+    #[test]
+    fn junit_reports_failures_and_errors() {
+        let xml = sample_report().to_junit();
+        assert!(xml.contains("<testsuite name=\"arith\" tests=\"5\" failures=\"2\" errors=\"1\""));
+        assert!(xml.contains("<testcase name=\"adds\" classname=\"arith\""));
+        assert!(xml.contains("<failure message=\"Output stack does not match\">"));
+        assert!(xml.contains("[0] expected 4, got 3"));
+        assert!(xml.contains("memory[0:100] expected 8, got 7"));
+        assert!(xml.contains("<error message=\"A test case needs a program or a procedure\">"));
+    }
+
+    #[test]
+    fn tap_numbers_every_case() {
+        let tap = sample_report().to_tap();
+        let lines: Vec<&str> = tap.lines().collect();
+        assert_eq!(lines[..3], ["TAP version 13", "1..5", "ok 1 - adds"]);
+        assert!(lines.contains(&"not ok 2 - adds wrong"));
+        assert!(lines.contains(&"      [0] expected 4, got 3"));
+        assert!(lines.contains(&"  status: \"error\""));
+        assert_eq!(lines.last(), Some(&"# 2 passed, 2 failed, 1 errors"));
+    }
+
+    #[test]
+    fn cancelled_suites_start_no_cases() {
+        let suite: TestSuite = serde_json::from_value(json!({
+            "cases": [{ "name": "adds", "program": "begin push.1 push.2 add end" }]
+        }))
+        .unwrap();
+        let progress = Progress::default();
+        progress.cancel();
+        assert_eq!(run_test_suite(&PLAYGROUND, &suite, &progress).total, 0);
+    }
+}
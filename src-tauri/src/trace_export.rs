@@ -0,0 +1,378 @@
+use crate::diagnostics::Diagnostic;
+use crate::playground::{ErrorKind, ExecutionRequest};
+use miden_air::trace::{
+    decoder::{
+        ADDR_COL_IDX, GROUP_COUNT_COL_IDX, IN_SPAN_COL_IDX, NUM_OP_BITS, OP_BITS_OFFSET,
+        OP_INDEX_COL_IDX,
+    },
+    stack::B0_COL_IDX,
+    CHIPLETS_OFFSET, CLK_COL_IDX, CTX_COL_IDX, DECODER_TRACE_OFFSET, FMP_COL_IDX,
+    IN_SYSCALL_COL_IDX, STACK_TRACE_OFFSET,
+};
+use miden_core::{Felt, Operation, ONE};
+use miden_processor::NUM_RAND_ROWS;
+use miden_vm::{utils::Deserializable, ExecutionTrace};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::ops::Range;
+use winter_prover::{matrix::ColMatrix, Trace};
+
+/// Most rows a single export returns; longer ranges are cut short.
+const MAX_TRACE_ROWS: usize = 65_536;
+
+/// Binary exports start with this magic string, followed by [BINARY_VERSION].
+const BINARY_MAGIC: &[u8] = b"MTRC";
+const BINARY_VERSION: u8 = 1;
+
+/// Chiplets in the order of their selector prefixes: a row belongs to the chiplet whose index is
+/// the number of leading selector columns set to one.
+pub(crate) const CHIPLETS: [&str; 6] = [
+    "hasher",
+    "bitwise",
+    "memory",
+    "ace",
+    "kernel_rom",
+    "padding",
+];
+
+const STACK_TOP_COLUMNS: usize = 16;
+
+/// Columns of every format, in order. The binary format leaves out `operation`, which `opcode`
+/// already identifies, and stores `chiplet` as an index into [CHIPLETS].
+const COLUMNS: [&str; 12] = [
+    "cycle",
+    "context",
+    "fmp",
+    "in_syscall",
+    "operation",
+    "opcode",
+    "block_address",
+    "in_span",
+    "group_count",
+    "op_index",
+    "stack_depth",
+    "chiplet",
+];
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceFormat {
+    #[default]
+    Csv,
+    /// One JSON object per row.
+    Jsonl,
+    /// Little-endian `u64` columns; see the README for the layout.
+    Binary,
+}
+
+impl TraceFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            TraceFormat::Csv => "text/csv; charset=utf-8",
+            TraceFormat::Jsonl => "application/x-ndjson",
+            TraceFormat::Binary => "application/octet-stream",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TraceRequest {
+    #[serde(flatten)]
+    pub execution: ExecutionRequest,
+    #[serde(default)]
+    pub format: TraceFormat,
+    /// First cycle to export, 0 by default.
+    pub start_cycle: Option<u32>,
+    /// Cycle to stop before; the end of the trace by default.
+    pub end_cycle: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct TraceExport {
+    pub success: bool,
+    pub program_hash: Option<String>,
+    pub format: TraceFormat,
+    /// Rows in the whole trace, padding included. The trace ends with random rows, which are left
+    /// out.
+    pub trace_len: Option<usize>,
+    /// Cycles the program ran before the trace was padded.
+    pub cycles: Option<usize>,
+    /// The exported range, which may end before the requested one when it is longer than 65,536
+    /// rows.
+    pub start_cycle: Option<u32>,
+    pub end_cycle: Option<u32>,
+    /// The exported rows of a `csv` or `jsonl` export.
+    pub data: Option<String>,
+    /// The exported rows of a `binary` export.
+    pub bytes: Option<Vec<u8>>,
+    pub error: Option<String>,
+    pub error_kind: Option<ErrorKind>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl TraceExport {
+    pub fn failure(
+        format: TraceFormat,
+        error_kind: ErrorKind,
+        error: String,
+        diagnostics: Vec<Diagnostic>,
+    ) -> Self {
+        TraceExport {
+            success: false,
+            program_hash: None,
+            format,
+            trace_len: None,
+            cycles: None,
+            start_cycle: None,
+            end_cycle: None,
+            data: None,
+            bytes: None,
+            error: Some(error),
+            error_kind: Some(error_kind),
+            diagnostics,
+        }
+    }
+}
+
+/// One row of the main trace. The chiplets fill their rows independently of the operations, so
+/// `chiplet` says which chiplet occupies the row rather than which one the operation used.
+struct TraceRow {
+    cycle: u64,
+    context: u64,
+    fmp: u64,
+    in_syscall: bool,
+    opcode: u8,
+    block_address: u64,
+    in_span: bool,
+    group_count: u64,
+    op_index: u64,
+    stack_depth: u64,
+    stack: [Felt; STACK_TOP_COLUMNS],
+    chiplet: usize,
+}
+
+#[derive(Serialize)]
+struct JsonRow<'a> {
+    cycle: u64,
+    context: u64,
+    fmp: u64,
+    in_syscall: bool,
+    operation: &'a str,
+    opcode: u8,
+    block_address: u64,
+    in_span: bool,
+    group_count: u64,
+    op_index: u64,
+    stack_depth: u64,
+    /// Field elements can exceed the integers JSON parsers read exactly, so they are strings.
+    stack: Vec<String>,
+    chiplet: &'a str,
+}
+
+/// The index into [CHIPLETS] of the chiplet filling `row`.
+pub(crate) fn chiplet_at(columns: &ColMatrix<Felt>, row: usize) -> usize {
+    (0..CHIPLETS.len() - 1)
+        .take_while(|&selector| columns.get(CHIPLETS_OFFSET + selector, row) == ONE)
+        .count()
+}
+
+fn read_row(columns: &ColMatrix<Felt>, row: usize) -> TraceRow {
+    let column = |index: usize| columns.get(index, row);
+    let decoder = |index: usize| column(DECODER_TRACE_OFFSET + index).as_int();
+
+    let opcode = (0..NUM_OP_BITS)
+        .map(|bit| (decoder(OP_BITS_OFFSET + bit) as u8) << bit)
+        .sum();
+
+    TraceRow {
+        cycle: column(CLK_COL_IDX).as_int(),
+        context: column(CTX_COL_IDX).as_int(),
+        fmp: column(FMP_COL_IDX).as_int(),
+        in_syscall: column(IN_SYSCALL_COL_IDX) == ONE,
+        opcode,
+        block_address: decoder(ADDR_COL_IDX),
+        in_span: decoder(IN_SPAN_COL_IDX) == 1,
+        group_count: decoder(GROUP_COUNT_COL_IDX),
+        op_index: decoder(OP_INDEX_COL_IDX),
+        stack_depth: column(STACK_TRACE_OFFSET + B0_COL_IDX).as_int(),
+        stack: std::array::from_fn(|i| column(STACK_TRACE_OFFSET + i)),
+        chiplet: chiplet_at(columns, row),
+    }
+}
+
+/// Names each opcode after its operation, without immediate values.
+fn operation_names() -> Vec<String> {
+    (0..1u8 << NUM_OP_BITS)
+        .map(|opcode| {
+            // Operations with an immediate value read it after the opcode, so pad with zeros
+            let mut bytes = [0; 9];
+            bytes[0] = opcode;
+            match Operation::read_from_bytes(&bytes) {
+                Ok(op) => {
+                    let name = op.to_string();
+                    match name.split_once('(') {
+                        Some((name, _)) => name.to_string(),
+                        None => name,
+                    }
+                }
+                Err(_) => "unknown".to_string(),
+            }
+        })
+        .collect()
+}
+
+fn stack_header() -> impl Iterator<Item = String> {
+    (0..STACK_TOP_COLUMNS).map(|i| format!("s{i}"))
+}
+
+fn write_csv(columns: &ColMatrix<Felt>, rows: Range<usize>) -> String {
+    let names = operation_names();
+    let header: Vec<String> = COLUMNS[..COLUMNS.len() - 1]
+        .iter()
+        .map(|name| name.to_string())
+        .chain(stack_header())
+        .chain(["chiplet".to_string()])
+        .collect();
+
+    let mut csv = header.join(",");
+    csv.push('\n');
+    for row in rows {
+        let row = read_row(columns, row);
+        let _ = write!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{},{},",
+            row.cycle,
+            row.context,
+            row.fmp,
+            row.in_syscall as u8,
+            names[row.opcode as usize],
+            row.opcode,
+            row.block_address,
+            row.in_span as u8,
+            row.group_count,
+            row.op_index,
+            row.stack_depth,
+        );
+        for value in row.stack {
+            let _ = write!(csv, "{},", value.as_int());
+        }
+        csv.push_str(CHIPLETS[row.chiplet]);
+        csv.push('\n');
+    }
+    csv
+}
+
+fn write_jsonl(columns: &ColMatrix<Felt>, rows: Range<usize>) -> String {
+    let names = operation_names();
+    let mut jsonl = String::new();
+    for row in rows {
+        let row = read_row(columns, row);
+        let json = JsonRow {
+            cycle: row.cycle,
+            context: row.context,
+            fmp: row.fmp,
+            in_syscall: row.in_syscall,
+            operation: &names[row.opcode as usize],
+            opcode: row.opcode,
+            block_address: row.block_address,
+            in_span: row.in_span,
+            group_count: row.group_count,
+            op_index: row.op_index,
+            stack_depth: row.stack_depth,
+            stack: row.stack.iter().map(|value| value.to_string()).collect(),
+            chiplet: CHIPLETS[row.chiplet],
+        };
+        jsonl.push_str(&serde_json::to_string(&json).unwrap());
+        jsonl.push('\n');
+    }
+    jsonl
+}
+
+/// Writes the magic string and version, the column names, each prefixed with its length in one
+/// byte, the number of rows as a `u32`, then every row as one `u64` per column, all little-endian.
+fn write_binary(columns: &ColMatrix<Felt>, rows: Range<usize>) -> Vec<u8> {
+    let names: Vec<String> = COLUMNS
+        .iter()
+        .filter(|&&name| name != "operation" && name != "chiplet")
+        .map(|name| name.to_string())
+        .chain(stack_header())
+        .chain(["chiplet".to_string()])
+        .collect();
+
+    let mut bytes = Vec::with_capacity(rows.len() * names.len() * 8 + 256);
+    bytes.extend_from_slice(BINARY_MAGIC);
+    bytes.push(BINARY_VERSION);
+    bytes.extend_from_slice(&(names.len() as u16).to_le_bytes());
+    for name in &names {
+        bytes.push(name.len() as u8);
+        bytes.extend_from_slice(name.as_bytes());
+    }
+    bytes.extend_from_slice(&(rows.len() as u32).to_le_bytes());
+
+    for row in rows {
+        let row = read_row(columns, row);
+        let values = [
+            row.cycle,
+            row.context,
+            row.fmp,
+            row.in_syscall as u64,
+            row.opcode as u64,
+            row.block_address,
+            row.in_span as u64,
+            row.group_count,
+            row.op_index,
+            row.stack_depth,
+        ]
+        .into_iter()
+        .chain(row.stack.iter().map(|value| value.as_int()))
+        .chain([row.chiplet as u64]);
+        for value in values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+/// Checks the requested range before the program is run.
+pub fn validate_range(request: &TraceRequest) -> Result<(), String> {
+    match (request.start_cycle, request.end_cycle) {
+        (Some(start), Some(end)) if end < start => Err(format!(
+            "end_cycle ({end}) must not be before start_cycle ({start})"
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Encodes the rows of `trace` in the requested range and format.
+pub fn export_trace(trace: &ExecutionTrace, request: &TraceRequest) -> TraceExport {
+    let columns = trace.main_segment();
+    // The last rows are random values that hide the trace from the verifier
+    let trace_len = trace.get_trace_len() - NUM_RAND_ROWS;
+    let start = (request.start_cycle.unwrap_or(0) as usize).min(trace_len);
+    let end = request
+        .end_cycle
+        .map_or(trace_len, |end| end as usize)
+        .min(trace_len)
+        .min(start + MAX_TRACE_ROWS);
+
+    let (data, bytes) = match request.format {
+        TraceFormat::Csv => (Some(write_csv(columns, start..end)), None),
+        TraceFormat::Jsonl => (Some(write_jsonl(columns, start..end)), None),
+        TraceFormat::Binary => (None, Some(write_binary(columns, start..end))),
+    };
+
+    TraceExport {
+        success: true,
+        program_hash: Some(trace.program_hash().to_string()),
+        format: request.format,
+        trace_len: Some(trace_len),
+        cycles: Some(trace.trace_len_summary().main_trace_len()),
+        start_cycle: Some(start as u32),
+        end_cycle: Some(end as u32),
+        data,
+        bytes,
+        error: None,
+        error_kind: None,
+        diagnostics: Vec::new(),
+    }
+}
@@ -0,0 +1,86 @@
+use crate::trace_export::{chiplet_at, CHIPLETS};
+use miden_vm::ExecutionTrace;
+use serde::Serialize;
+use winter_prover::Trace;
+
+/// A part of the trace whose rows are filled independently of the others; the longest one sets
+/// the length of the whole trace.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceSegment {
+    /// The system, decoder and stack columns, one row per cycle.
+    Main,
+    Chiplets,
+    RangeChecker,
+}
+
+/// Rows used by each chiplet.
+#[derive(Serialize, Clone, Debug)]
+pub struct ChipletRows {
+    pub hasher: usize,
+    pub bitwise: usize,
+    pub memory: usize,
+    pub ace: usize,
+    pub kernel_rom: usize,
+    /// All of the above, plus the padding row the chiplets always end with.
+    pub total: usize,
+}
+
+/// How long each segment of the trace is, and which one the padded length follows from.
+#[derive(Serialize, Clone, Debug)]
+pub struct TraceSummary {
+    pub main_trace_len: usize,
+    pub chiplets: ChipletRows,
+    pub range_checker_len: usize,
+    /// The longest of the segments.
+    pub trace_len: usize,
+    /// `trace_len` plus the random rows the trace ends with, rounded up to a power of two.
+    pub padded_trace_len: usize,
+    /// Share of `padded_trace_len` taken by padding, from 0 to 100.
+    pub padding_percentage: usize,
+    /// The segment `trace_len` comes from; when segments are equally long, the first of `main`,
+    /// `chiplets` and `range_checker`.
+    pub limiting_segment: TraceSegment,
+}
+
+pub fn trace_summary(trace: &ExecutionTrace) -> TraceSummary {
+    let lengths = trace.trace_len_summary();
+    let chiplets = lengths.chiplets_trace_len();
+
+    // The VM counts the ACE chiplet as part of memory, so its rows are told apart by their
+    // selectors. It sits between the memory chiplet and the kernel ROM.
+    let columns = trace.main_segment();
+    let memory_start = chiplets.hash_chiplet_len() + chiplets.bitwise_chiplet_len();
+    let memory_end = memory_start + chiplets.memory_chiplet_len();
+    let ace = (memory_start..memory_end)
+        .filter(|&row| CHIPLETS[chiplet_at(columns, row)] == "ace")
+        .count();
+
+    let segments = [
+        (TraceSegment::Main, lengths.main_trace_len()),
+        (TraceSegment::Chiplets, chiplets.trace_len()),
+        (TraceSegment::RangeChecker, lengths.range_trace_len()),
+    ];
+    let trace_len = lengths.trace_len();
+    let limiting_segment = segments
+        .iter()
+        .find(|(_, len)| *len == trace_len)
+        .map_or(TraceSegment::Main, |(segment, _)| *segment);
+
+    TraceSummary {
+        main_trace_len: lengths.main_trace_len(),
+        chiplets: ChipletRows {
+            hasher: chiplets.hash_chiplet_len(),
+            bitwise: chiplets.bitwise_chiplet_len(),
+            memory: chiplets.memory_chiplet_len() - ace,
+            ace,
+            kernel_rom: chiplets.kernel_rom_len(),
+            total: chiplets.trace_len(),
+        },
+        range_checker_len: lengths.range_trace_len(),
+        trace_len,
+        padded_trace_len: lengths.padded_trace_len(),
+        padding_percentage: lengths.padding_percentage(),
+        limiting_segment,
+    }
+}
@@ -1,19 +1,41 @@
-use crate::client::{
-    execute_program_impl, generate_proof_impl, ExecutionRequest, ExecutionResult, ProofResult,
+use crate::artifacts::ExportRequest;
+use crate::compile::CompileRequest;
+use crate::debugger::{
+    debug_command_impl, end_debug_session_impl, start_debug_session_impl, DebugCommand,
+    DebugResult, DebugStartRequest,
 };
+use crate::disassembler::DisassembleRequest;
+use crate::examples::ExampleResult;
+use crate::jobs::{JobInfo, JobKind, JobMessage, JobRequest, JobResult, TaskOutcome, JOBS};
+use crate::limits::LIMITS;
+use crate::mast_tree::MastTreeRequest;
+use crate::playground::{ErrorKind, ExecutionRequest, VerifyRequest, PLAYGROUND};
+use crate::storage::{
+    create_program_impl, delete_program_impl, list_programs_impl, load_program_impl,
+    load_program_version_impl, rename_program_impl, update_program_impl, RenameProgramRequest,
+    SaveProgramRequest,
+};
+use crate::test_runner::{TestReportFormat, TestRequest};
+use crate::trace_export::{TraceExport, TraceRequest};
 
 #[cfg(feature = "web_server")]
 use axum::{
-    extract::Json,
-    http::Method,
-    response::Json as ResponseJson,
-    routing::{get, post},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        DefaultBodyLimit, Json, Path,
+    },
+    http::{header, Method, StatusCode},
+    response::{IntoResponse, Json as ResponseJson, Response},
+    routing::{delete, get, post},
     Router,
 };
 
 #[cfg(feature = "web_server")]
 use tower_http::cors::{Any, CorsLayer};
 
+#[cfg(feature = "web_server")]
+use serde::{Deserialize, Serialize};
+
 #[cfg(feature = "web_server")]
 use serde_json::json;
 
@@ -24,16 +46,67 @@ use std::net::SocketAddr;
 pub async fn start_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
     let cors = CorsLayer::new()
         .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
         .allow_headers(Any);
 
     let app = Router::new()
         .route("/health", get(health_handler))
         .route("/api/examples", get(examples_handler))
+        .route("/api/examples/:id", get(example_handler))
         .route("/api/execute", post(execute_handler))
         .route("/api/prove", post(prove_handler))
+        .route("/api/verify", post(verify_handler))
+        .route("/api/compile", post(compile_handler))
+        .route("/api/export", post(export_handler))
+        .route("/api/mast", post(mast_handler))
+        .route("/api/disassemble", post(disassemble_handler))
+        .route("/api/test", post(test_handler))
+        .route("/api/trace", post(trace_handler))
+        .route(
+            "/api/jobs",
+            get(queue_status_handler).post(submit_job_handler),
+        )
+        .route("/api/jobs/stream", get(job_stream_handler))
+        .route(
+            "/api/jobs/:id",
+            get(job_status_handler).delete(cancel_job_handler),
+        )
+        .route("/api/debug", post(debug_start_handler))
+        .route("/api/debug/:session_id", delete(debug_end_handler))
+        .route(
+            "/api/debug/:session_id/:command",
+            post(debug_command_handler),
+        )
+        .route(
+            "/api/programs",
+            get(list_programs_handler).post(create_program_handler),
+        )
+        .route(
+            "/api/programs/:id",
+            get(load_program_handler)
+                .put(update_program_handler)
+                .patch(rename_program_handler)
+                .delete(delete_program_handler),
+        )
+        .route(
+            "/api/programs/:id/versions/:version",
+            get(load_program_version_handler),
+        )
+        .layer(DefaultBodyLimit::max(LIMITS.max_body_bytes))
         .layer(cors);
 
+    // Run the examples before serving them, so a broken catalog shows up in the server log
+    for problem in tokio::task::spawn_blocking(|| PLAYGROUND.validate_examples()).await? {
+        eprintln!("⚠️  {problem}");
+    }
+
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
     println!("🚀 Miden VM API Server starting on http://0.0.0.0:{}", port);
 
@@ -53,406 +126,448 @@ async fn health_handler() -> ResponseJson<serde_json::Value> {
 }
 
 #[cfg(feature = "web_server")]
-async fn examples_handler() -> ResponseJson<serde_json::Value> {
-    let prime_generator = r#"use.std::sys
-
-# append the current number to the prime list
-proc.append
-    # initial state
-    # [prime, i, n, primes..]
-
-    # [prime, prime, i, n, primes..]
-    dup
-
-    # [i, prime, prime, i, n, primes..]
-    dup.2
-
-    # [prime, i, n, primes..]
-    mem_store
-
-    # [i++, n, primes..]
-    swap.2
-    swap
-    add.1
-end
-
-# push a boolean on whether or not the program should continue
-proc.should_continue
-    # initial state
-    # [i, n, primes..]
-
-    # [i, n, i, n, primes..]
-    dup.1
-    dup.1
-
-    # [should_continue, i, n, primes..]
-    neq
-end
-
-# define if check should continue
-# will return two flags: one if the loop should continue, the other if candidate is prime
-proc.is_not_prime_should_continue
-    # initial state
-    # [j, candidate, i, n, primes..]
-
-    # load the current prime
-    # [prime, j, candidate, i, n, primes..]
-    dup
-    mem_load
-
-    # push return flags
-    # [continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-    push.0.1
-
-    # a composite number have its smallest prime squared lesser than itself.
-    # if the squared prime is bigger than the candidate, and provided we iterate
-    # a list of ordered primes, then the number is a prime.
-    #
-    # this will also protect the algorithm from overflowing the list of current list of primes
-    # because the squared prime will always halt the iteration before the end of the list is
-    # reached
-    #
-    # [squared prime, continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-    dup.2
-    dup
-    mul
-    # [candidate, squared prime, continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-    dup.5
-    # [continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-    gt
-    if.true
-        drop
-        drop
-        push.1.0
-    end
-
-    # check mod only if should continue loop
-    dup
-    if.true
-        # [remainder, continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-        dup.4
-        dup.3
-        u32assert2 u32mod
-
-        # if remainder is zero, then the number is divisible by prime; hence isn't prime
-        # [continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-        eq.0
-        if.true
-            drop
-            drop
-            push.0.0
-        end
-    end
-
-    # [continue loop?, is prime?, j, candidate, i, n, primes..]
-    swap.2
-    drop
-    swap
-end
-
-# check if current candidate isn't a prime
-proc.is_not_prime
-    # initial state
-    # [candidate, i, n, primes..]
-
-    # create a counter `j` to iterate over primes
-    # [j, candidate, i, n, primes..]
-    push.0
-
-    exec.is_not_prime_should_continue
-    while.true
-        # [j, candidate, i, n, primes..]
-        drop
-        add.1
-
-        # [is prime?, j, candidate, i, n, primes..]
-        exec.is_not_prime_should_continue
-    end
-
-    # [is not prime?, candidate, i, n, primes..]
-    swap
-    drop
-    eq.0
-end
-
-# calculate and push next prime to the stack
-proc.next
-    # initial state
-    # [i, n, primes..]
-
-    # create a candidate
-    # [candidate, i, n, primes..]
-    dup.2
-    add.2
-
-    exec.is_not_prime
-    while.true
-        # [candidate, i, n, primes..]
-        add.2
-        exec.is_not_prime
-    end
-
-    # [i, n, primes..]
-    exec.append
-end
-
-# the stack is expected to contain on its top the desired primes count. this can be achieved via the
-# *.inputs file.
-#
-# the end of the program will return a stack containing all the primes, up to the nth argument.
-#
-# example:
-#
-# input:
-# [50, ..]
-#
-# output:
-# [229, 227, 223, 211, 199, 197, 193, 191, 181, 179, 173, 167, 163, 157, 151, 149]
-begin
-    # create a counter `i`
-    push.0
-
-    # 2 and 3 are the unique sequential primes. by pushing these manually, we can iterate
-    # the candidates in chunks of 2
-
-    # append first known prime
-    push.2
-    exec.append
-
-    # append second known prime
-    push.3
-    exec.append
-
-    # find next primes until limit is reached
-    exec.should_continue
-    while.true
-        exec.next
-        exec.should_continue
-    end
-
-    # drop the counters
-    drop
-    drop
-
-    # Truncate stack to make constraints happy
-    exec.sys::truncate_stack
-end"#;
-
-    let prime_generator = r#"use.std::sys
-
-# append the current number to the prime list
-proc.append
-    # initial state
-    # [prime, i, n, primes..]
-
-    # [prime, prime, i, n, primes..]
-    dup
-
-    # [i, prime, prime, i, n, primes..]
-    dup.2
-
-    # [prime, i, n, primes..]
-    mem_store
-
-    # [i++, n, primes..]
-    swap.2
-    swap
-    add.1
-end
-
-# push a boolean on whether or not the program should continue
-proc.should_continue
-    # initial state
-    # [i, n, primes..]
-
-    # [i, n, i, n, primes..]
-    dup.1
-    dup.1
-
-    # [should_continue, i, n, primes..]
-    neq
-end
-
-# define if check should continue
-# will return two flags: one if the loop should continue, the other if candidate is prime
-proc.is_not_prime_should_continue
-    # initial state
-    # [j, candidate, i, n, primes..]
-
-    # load the current prime
-    # [prime, j, candidate, i, n, primes..]
-    dup
-    mem_load
-
-    # push return flags
-    # [continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-    push.0.1
-
-    # a composite number have its smallest prime squared lesser than itself.
-    # if the squared prime is bigger than the candidate, and provided we iterate
-    # a list of ordered primes, then the number is a prime.
-    #
-    # this will also protect the algorithm from overflowing the list of current list of primes
-    # because the squared prime will always halt the iteration before the end of the list is
-    # reached
-    #
-    # [squared prime, continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-    dup.2
-    dup
-    mul
-    # [candidate, squared prime, continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-    dup.5
-    # [continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-    gt
-    if.true
-        drop
-        drop
-        push.1.0
-    end
-
-    # check mod only if should continue loop
-    dup
-    if.true
-        # [remainder, continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-        dup.4
-        dup.3
-        u32assert2 u32mod
-
-        # if remainder is zero, then the number is divisible by prime; hence isn't prime
-        # [continue loop?, is prime?, prime, j, candidate, i, n, primes..]
-        eq.0
-        if.true
-            drop
-            drop
-            push.0.0
-        end
-    end
-
-    # [continue loop?, is prime?, j, candidate, i, n, primes..]
-    swap.2
-    drop
-    swap
-end
-
-# check if current candidate isn't a prime
-proc.is_not_prime
-    # initial state
-    # [candidate, i, n, primes..]
-
-    # create a counter `j` to iterate over primes
-    # [j, candidate, i, n, primes..]
-    push.0
-
-    exec.is_not_prime_should_continue
-    while.true
-        # [j, candidate, i, n, primes..]
-        drop
-        add.1
-
-        # [is prime?, j, candidate, i, n, primes..]
-        exec.is_not_prime_should_continue
-    end
-
-    # [is not prime?, candidate, i, n, primes..]
-    swap
-    drop
-    eq.0
-end
-
-# calculate and push next prime to the stack
-proc.next
-    # initial state
-    # [i, n, primes..]
-
-    # create a candidate
-    # [candidate, i, n, primes..]
-    dup.2
-    add.2
-
-    exec.is_not_prime
-    while.true
-        # [candidate, i, n, primes..]
-        add.2
-        exec.is_not_prime
-    end
-
-    # [i, n, primes..]
-    exec.append
-end
-
-# the stack is expected to contain on its top the desired primes count. this can be achieved via the
-# *.inputs file.
-#
-# the end of the program will return a stack containing all the primes, up to the nth argument.
-#
-# example:
-#
-# input:
-# [50, ..]
-#
-# output:
-# [229, 227, 223, 211, 199, 197, 193, 191, 181, 179, 173, 167, 163, 157, 151, 149]
-begin
-    # create a counter `i`
-    push.0
-
-    # 2 and 3 are the unique sequential primes. by pushing these manually, we can iterate
-    # the candidates in chunks of 2
-
-    # append first known prime
-    push.2
-    exec.append
-
-    # append second known prime
-    push.3
-    exec.append
-
-    # find next primes until limit is reached
-    exec.should_continue
-    while.true
-        exec.next
-        exec.should_continue
-    end
-
-    # drop the counters
-    drop
-    drop
-
-    # Truncate stack to make constraints happy
-    exec.sys::truncate_stack
-end"#;
-
-    let examples = vec![
-        ("Basic Addition", "# Adds 3 + 5 = 8\nbegin\n    push.3\n    push.5\n    add\nend"),
-        ("Input Stack Demo", "# Adds two inputs: Try with [\"10\", \"20\"] → 30\nbegin\n    add\nend"),
-        ("Fibonacci Numbers", "# Computes F(8) using repeat loop\n\
-        # Input: { \"operand_stack\": [\"1\"] }\n\
-        begin\n
-        # This code computes 69th Fibonacci number\n
-        repeat.68\n
-            swap dup.1 add\n
-            end\n
-        end"),
-        ("Prime Generator", prime_generator),
-        ("Conditional Logic", "# Keeps larger of 15 and 10 (result: 15)\nbegin\n    push.15\n    push.10\n    dup.1 gt\n    if.true\n        swap\n    end\n    drop\nend"),
-        ("Memory Operations", "# Stores 42 and 100 in memory, sums them (result: 142)\nbegin\n    push.42 push.0 mem_store\n    push.100 push.1 mem_store\n    push.0 mem_load\n    push.1 mem_load\n    add\nend"),
-        ("Stack Manipulation", "# Leaves [3,3] on the stack\nbegin\n    push.1 push.2 push.3 push.4\n    swap.2\n    drop\n    dup\n    swap.2\n    drop\n    drop\nend"),
-        ("Counter with Input", "# Adds 5 to input. Try [\"7\"] → 12\nbegin\n    push.5\n    add\nend"),
-    ];
-
-    ResponseJson(json!(examples))
-}
-
-#[cfg(feature = "web_server")]
-async fn execute_handler(Json(payload): Json<ExecutionRequest>) -> ResponseJson<ExecutionResult> {
-    let inputs_json = payload.inputs.as_ref().map(|v| v.to_string());
-    let result = execute_program_impl(&payload.program, inputs_json.as_deref());
-    ResponseJson(result)
-}
-
-#[cfg(feature = "web_server")]
-async fn prove_handler(Json(payload): Json<ExecutionRequest>) -> ResponseJson<ProofResult> {
-    let inputs_json = payload.inputs.as_ref().map(|v| v.to_string());
-    let result = generate_proof_impl(&payload.program, inputs_json.as_deref());
-    ResponseJson(result)
+async fn examples_handler() -> ResponseJson<ExampleResult> {
+    ResponseJson(PLAYGROUND.examples().list())
+}
+
+#[cfg(feature = "web_server")]
+async fn example_handler(Path(id): Path<String>) -> ResponseJson<ExampleResult> {
+    ResponseJson(PLAYGROUND.examples().find(&id))
+}
+
+#[cfg(feature = "web_server")]
+async fn execute_handler(
+    Json(payload): Json<ExecutionRequest>,
+) -> (StatusCode, ResponseJson<serde_json::Value>) {
+    run_job_to_completion(JobKind::Execute, payload).await
+}
+
+#[cfg(feature = "web_server")]
+async fn prove_handler(
+    Json(payload): Json<ExecutionRequest>,
+) -> (StatusCode, ResponseJson<serde_json::Value>) {
+    run_job_to_completion(JobKind::Prove, payload).await
+}
+
+/// Queues a job and waits for its result, so synchronous requests share the worker pool.
+#[cfg(feature = "web_server")]
+async fn run_job_to_completion(
+    kind: JobKind,
+    request: ExecutionRequest,
+) -> (StatusCode, ResponseJson<serde_json::Value>) {
+    let submitted = JOBS.submit(JobRequest { kind, request });
+    let Some(job) = submitted.job else {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            ResponseJson(json!(submitted)),
+        );
+    };
+
+    let id = job.id;
+    let finished = tokio::task::spawn_blocking(move || JOBS.wait(id))
+        .await
+        .ok()
+        .flatten();
+    match finished {
+        Some(JobInfo {
+            result: Some(result),
+            ..
+        }) => (StatusCode::OK, ResponseJson(result)),
+        Some(job) => (
+            StatusCode::OK,
+            ResponseJson(json!({
+                "success": false,
+                "error": job.error.unwrap_or_else(|| format!("Job {id} was cancelled")),
+            })),
+        ),
+        None => (
+            StatusCode::OK,
+            ResponseJson(json!({ "success": false, "error": format!("Job {id} was lost") })),
+        ),
+    }
+}
+
+#[cfg(feature = "web_server")]
+async fn submit_job_handler(
+    Json(payload): Json<JobRequest>,
+) -> (StatusCode, ResponseJson<JobResult>) {
+    let result = JOBS.submit(payload);
+    let status = if result.success {
+        StatusCode::ACCEPTED
+    } else {
+        StatusCode::TOO_MANY_REQUESTS
+    };
+    (status, ResponseJson(result))
+}
+
+#[cfg(feature = "web_server")]
+async fn queue_status_handler() -> ResponseJson<JobResult> {
+    ResponseJson(JOBS.queue_status())
+}
+
+#[cfg(feature = "web_server")]
+async fn job_status_handler(Path(id): Path<u64>) -> ResponseJson<JobResult> {
+    ResponseJson(JOBS.status(id))
+}
+
+#[cfg(feature = "web_server")]
+async fn cancel_job_handler(Path(id): Path<u64>) -> ResponseJson<JobResult> {
+    ResponseJson(JOBS.cancel(id))
+}
+
+/// Messages a client can send while following a job over a WebSocket.
+#[cfg(feature = "web_server")]
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamCommand {
+    Cancel,
+}
+
+/// Runs a job over a WebSocket: the client sends a job request as its first message and receives
+/// the job's progress as it happens, followed by the finished job.
+#[cfg(feature = "web_server")]
+async fn job_stream_handler(ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(stream_job)
+}
+
+#[cfg(feature = "web_server")]
+async fn send_message(socket: &mut WebSocket, message: &JobMessage) -> bool {
+    let text = serde_json::to_string(message).unwrap();
+    socket.send(Message::Text(text)).await.is_ok()
+}
+
+#[cfg(feature = "web_server")]
+async fn stream_job(mut socket: WebSocket) {
+    let request = match socket.recv().await {
+        Some(Ok(Message::Text(text))) => serde_json::from_str::<JobRequest>(&text)
+            .map_err(|e| format!("Invalid job request: {e}")),
+        Some(Ok(_)) => Err("Expected a JSON job request".to_string()),
+        _ => return,
+    };
+    let submitted = match request {
+        Ok(request) => JOBS.submit(request),
+        Err(error) => {
+            send_message(&mut socket, &JobMessage::Error { error }).await;
+            return;
+        }
+    };
+    let Some(job) = submitted.job else {
+        let error = submitted.error.unwrap_or_default();
+        send_message(&mut socket, &JobMessage::Error { error }).await;
+        return;
+    };
+
+    let id = job.id;
+    if !send_message(&mut socket, &JobMessage::Submitted { job }).await {
+        JOBS.cancel(id);
+        return;
+    }
+
+    let (sender, mut updates) = tokio::sync::mpsc::unbounded_channel();
+    tokio::task::spawn_blocking(move || JOBS.follow(id, |message| sender.send(message).is_ok()));
+
+    loop {
+        tokio::select! {
+            update = updates.recv() => {
+                // The channel closes once the finished job has been sent
+                let Some(update) = update else { break };
+                if !send_message(&mut socket, &update).await {
+                    JOBS.cancel(id);
+                    break;
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    match serde_json::from_str::<StreamCommand>(&text) {
+                        // The finished job still follows, reporting the cancellation
+                        Ok(StreamCommand::Cancel) => {
+                            JOBS.cancel(id);
+                        }
+                        Err(e) => {
+                            let error = format!("Invalid message: {e}");
+                            send_message(&mut socket, &JobMessage::Error { error }).await;
+                        }
+                    }
+                }
+                // Nobody is left to receive the result once the client disconnects
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                    JOBS.cancel(id);
+                    break;
+                }
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+}
+
+#[cfg(feature = "web_server")]
+async fn verify_handler(Json(payload): Json<VerifyRequest>) -> Response {
+    run_blocking(move || PLAYGROUND.verify(&payload)).await
+}
+
+#[cfg(feature = "web_server")]
+async fn compile_handler(Json(payload): Json<CompileRequest>) -> Response {
+    run_blocking(move || PLAYGROUND.compile(&payload)).await
+}
+
+#[cfg(feature = "web_server")]
+async fn export_handler(Json(payload): Json<ExportRequest>) -> Response {
+    run_blocking(move || PLAYGROUND.export(&payload)).await
+}
+
+#[cfg(feature = "web_server")]
+async fn mast_handler(Json(payload): Json<MastTreeRequest>) -> Response {
+    run_blocking(move || PLAYGROUND.mast_tree(&payload)).await
+}
+
+#[cfg(feature = "web_server")]
+async fn disassemble_handler(Json(payload): Json<DisassembleRequest>) -> Response {
+    run_blocking(move || PLAYGROUND.disassemble(&payload)).await
+}
+
+/// Runs a test suite on the worker pool and returns the report in the requested format.
+#[cfg(feature = "web_server")]
+async fn test_handler(Json(payload): Json<TestRequest>) -> Response {
+    let format = payload.format;
+    let run = move || {
+        JOBS.run(JobKind::Test, move |progress| {
+            PLAYGROUND.test_with_progress(&payload.suite, progress)
+        })
+    };
+    let report = match tokio::task::spawn_blocking(run).await {
+        Ok(Ok(TaskOutcome::Finished(report))) => report,
+        Ok(Err(rejected)) => {
+            return (StatusCode::TOO_MANY_REQUESTS, ResponseJson(rejected)).into_response();
+        }
+        Ok(Ok(TaskOutcome::Cancelled)) => {
+            return ResponseJson(json!({
+                "success": false,
+                "error": "Cancelled",
+                "error_kind": ErrorKind::Cancelled,
+            }))
+            .into_response();
+        }
+        Ok(Ok(TaskOutcome::Panicked)) | Err(_) => {
+            let error = "Test run failed".to_string();
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ResponseJson(json!({ "success": false, "error": error })),
+            )
+                .into_response();
+        }
+    };
+    let content_type = match format {
+        TestReportFormat::Json => "application/json",
+        TestReportFormat::Junit => "application/xml",
+        TestReportFormat::Tap => "text/plain; charset=utf-8",
+    };
+    (
+        [(header::CONTENT_TYPE, content_type)],
+        report.render(format),
+    )
+        .into_response()
+}
+
+/// Runs a program on the worker pool and returns its execution trace in the requested format, with
+/// the exported range in the `x-trace-*` headers. Failures are returned as JSON.
+#[cfg(feature = "web_server")]
+async fn trace_handler(Json(payload): Json<TraceRequest>) -> Response {
+    let format = payload.format;
+    let run = move || {
+        JOBS.run(JobKind::Trace, move |progress| {
+            PLAYGROUND.trace_with_progress(&payload, progress)
+        })
+    };
+    let mut export = match tokio::task::spawn_blocking(run).await {
+        Ok(Ok(TaskOutcome::Finished(export))) => export,
+        Ok(Ok(TaskOutcome::Cancelled)) => TraceExport::failure(
+            format,
+            ErrorKind::Cancelled,
+            "Cancelled".to_string(),
+            Vec::new(),
+        ),
+        Ok(Err(rejected)) => {
+            return (StatusCode::TOO_MANY_REQUESTS, ResponseJson(rejected)).into_response();
+        }
+        Ok(Ok(TaskOutcome::Panicked)) | Err(_) => {
+            let error = "Trace export failed".to_string();
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ResponseJson(json!({ "success": false, "error": error })),
+            )
+                .into_response();
+        }
+    };
+    let body = export
+        .data
+        .take()
+        .map(String::into_bytes)
+        .or(export.bytes.take());
+    match (body, export.start_cycle, export.end_cycle, export.trace_len) {
+        (Some(body), Some(start), Some(end), Some(trace_len)) => {
+            let headers = [
+                (
+                    header::CONTENT_TYPE,
+                    export.format.content_type().to_string(),
+                ),
+                (
+                    header::HeaderName::from_static("x-trace-start"),
+                    start.to_string(),
+                ),
+                (
+                    header::HeaderName::from_static("x-trace-end"),
+                    end.to_string(),
+                ),
+                (
+                    header::HeaderName::from_static("x-trace-length"),
+                    trace_len.to_string(),
+                ),
+            ];
+            (headers, body).into_response()
+        }
+        _ => ResponseJson(export).into_response(),
+    }
+}
+
+/// Runs work that assembles or executes programs on the blocking thread pool, so it does not stall
+/// the async runtime, and answers with its result as JSON.
+#[cfg(feature = "web_server")]
+async fn run_blocking<T: Serialize + Send + 'static>(
+    task: impl FnOnce() -> T + Send + 'static,
+) -> Response {
+    match tokio::task::spawn_blocking(task).await {
+        Ok(result) => ResponseJson(result).into_response(),
+        Err(e) => {
+            let error = format!("Request failed: {e}");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                ResponseJson(json!({ "success": false, "error": error })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(feature = "web_server")]
+async fn debug_start_handler(Json(payload): Json<DebugStartRequest>) -> Response {
+    run_blocking(move || {
+        start_debug_session_impl(
+            &payload.program,
+            payload.inputs.as_ref(),
+            &payload.modules,
+            payload.breakpoints.unwrap_or_default(),
+            &payload.settings,
+        )
+    })
+    .await
+}
+
+#[cfg(feature = "web_server")]
+async fn debug_command_handler(Path((session_id, command)): Path<(u64, DebugCommand)>) -> Response {
+    run_blocking(move || debug_command_impl(session_id, command)).await
+}
+
+#[cfg(feature = "web_server")]
+async fn debug_end_handler(Path(session_id): Path<u64>) -> ResponseJson<DebugResult> {
+    ResponseJson(end_debug_session_impl(session_id))
+}
+
+#[cfg(feature = "web_server")]
+async fn list_programs_handler() -> Response {
+    run_blocking(list_programs_impl).await
+}
+
+#[cfg(feature = "web_server")]
+async fn create_program_handler(Json(payload): Json<SaveProgramRequest>) -> Response {
+    run_blocking(move || create_program_impl(payload)).await
+}
+
+#[cfg(feature = "web_server")]
+async fn load_program_handler(Path(id): Path<u64>) -> Response {
+    run_blocking(move || load_program_impl(id)).await
+}
+
+#[cfg(feature = "web_server")]
+async fn load_program_version_handler(Path((id, version)): Path<(u64, u32)>) -> Response {
+    run_blocking(move || load_program_version_impl(id, version)).await
+}
+
+#[cfg(feature = "web_server")]
+async fn update_program_handler(
+    Path(id): Path<u64>,
+    Json(payload): Json<SaveProgramRequest>,
+) -> Response {
+    run_blocking(move || update_program_impl(id, payload)).await
+}
+
+#[cfg(feature = "web_server")]
+async fn rename_program_handler(
+    Path(id): Path<u64>,
+    Json(payload): Json<RenameProgramRequest>,
+) -> Response {
+    run_blocking(move || rename_program_impl(id, &payload.name)).await
+}
+
+#[cfg(feature = "web_server")]
+async fn delete_program_handler(Path(id): Path<u64>) -> Response {
+    run_blocking(move || delete_program_impl(id)).await
+}
+
+#[cfg(all(test, feature = "web_server"))]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    fn wait_for(condition: impl Fn(&JobResult) -> bool) {
+        while !condition(&JOBS.queue_status()) {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn full_queues_answer_too_many_requests() {
+        let gate = Arc::new((Mutex::new(false), Condvar::new()));
+        let block = || {
+            let gate = gate.clone();
+            thread::spawn(move || {
+                JOBS.run(JobKind::Test, move |_| {
+                    let (open, opened) = &*gate;
+                    let mut open = open.lock().unwrap();
+                    while !*open {
+                        open = opened.wait(open).unwrap();
+                    }
+                })
+            })
+        };
+
+        // Occupy every worker first, so the jobs that follow stay queued
+        let queue = JOBS.queue_status().queue;
+        let mut blockers: Vec<_> = (0..queue.workers).map(|_| block()).collect();
+        wait_for(|status| status.queue.running == queue.workers);
+        blockers.extend((0..queue.capacity).map(|_| block()));
+        wait_for(|status| status.queue.queued == queue.capacity);
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let job = || JobRequest {
+            kind: JobKind::Execute,
+            request: ExecutionRequest::default(),
+        };
+        let (status, ResponseJson(submitted)) = runtime.block_on(submit_job_handler(Json(job())));
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert!(submitted.job.is_none());
+        let (status, _) = runtime.block_on(execute_handler(Json(job().request)));
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+        let (open, opened) = &*gate;
+        *open.lock().unwrap() = true;
+        opened.notify_all();
+        for blocker in blockers {
+            assert!(matches!(
+                blocker.join().unwrap(),
+                Ok(TaskOutcome::Finished(()))
+            ));
+        }
+    }
 }
@@ -24,6 +24,17 @@ interface ProofResult {
   total_time_ms?: number;
 }
 
+interface Example {
+  id: string;
+  title: string;
+  description?: string;
+  category?: string;
+  difficulty?: string;
+  source: string;
+  inputs?: any;
+  expected_stack?: string[];
+}
+
 // Environment detection
 const isTauri = () => {
   return typeof window !== 'undefined' && 
@@ -102,7 +113,7 @@ class WebAPIClient {
     }
   }
 
-  async getExamples(): Promise<[string, string][]> {
+  async getExamples(): Promise<Example[]> {
     try {
       const response = await fetch(`${this.baseUrl}/api/examples`);
       
@@ -110,16 +121,18 @@ class WebAPIClient {
         throw new Error(`HTTP error! status: ${response.status}`);
       }
       const data = await response.json();
-      console.log('new data', data);
-      return data;
+      if (!data.success) {
+        throw new Error(data.error);
+      }
+      return data.examples;
     } catch (error) {
       console.error('Failed to load examples from API:', error);
       return this.getFallbackExamples();
     }
   }
 
-  private getFallbackExamples(): [string, string][] {
-    return [
+  private getFallbackExamples(): Example[] {
+    const examples: [string, string][] = [
       [
         "Basic Addition",
         `# Adds 3 + 5 and leaves 8 on the stack
@@ -211,6 +224,7 @@ class WebAPIClient {
   end`
       ]
     ];
+    return examples.map(([title, source]) => ({ id: title, title, source }));
   }
 
 }
@@ -230,7 +244,7 @@ end`);
   const [proofResult, setProofResult] = useState<ProofResult | null>(null);
   const [isRunning, setIsRunning] = useState(false);
   const [isProving, setIsProving] = useState(false);
-  const [examples, setExamples] = useState<[string, string][]>([]);
+  const [examples, setExamples] = useState<Example[]>([]);
   const [activeTab, setActiveTab] = useState<'execution' | 'proof'>('execution');
   const [environment, setEnvironment] = useState<'web' | 'tauri'>('web');
   const [isConnected, setIsConnected] = useState(false);
@@ -262,8 +276,8 @@ end`);
     try {
       const { invoke } = await import("@tauri-apps/api/core");
       const examplesJson = await invoke("get_example_programs") as string;
-      const examplesList = JSON.parse(examplesJson) as [string, string][];
-      setExamples(examplesList);
+      const result = JSON.parse(examplesJson);
+      setExamples(result.examples ?? []);
     } catch (err) {
       console.error("Failed to load Tauri examples:", err);
       loadWebExamples();
@@ -371,23 +385,11 @@ end`);
     }
   }
 
-  function loadExample(exampleProgram: string, exampleName: string) {
-    setProgram(exampleProgram);
+  function loadExample(example: Example) {
+    setProgram(example.source);
     setResult(null);
     setProofResult(null);
-    
-    // Auto-populate inputs based on example type
-    if (exampleName.includes('Fibonacci')) {
-      setInputs('{\n  "operand_stack": ["1"]\n}');
-    } else if (exampleName.includes('Input Stack Demo')) {
-      setInputs('{\n  "operand_stack": ["10", "20"]\n}');
-    } else if (exampleName.includes('Counter with Input')) {
-      setInputs('{\n  "operand_stack": ["7"]\n}');
-    } else if (exampleName.includes('Prime Generator')) {
-      setInputs('{\n  "operand_stack": ["10"]\n}');
-    } else {
-      setInputs('{\n  "operand_stack": []\n}');
-    }
+    setInputs(JSON.stringify(example.inputs ?? { operand_stack: [] }, null, 2));
   }
 
   function formatStackOutput(outputs: string[]) {
@@ -416,14 +418,16 @@ end`);
               <select 
                 className="example-selector"
                 onChange={(e) => {
-                  const selectedExample = examples.find(ex => ex[0] === e.target.value);
-                  if (selectedExample) loadExample(selectedExample[1], selectedExample[0]);
+                  const selectedExample = examples.find(ex => ex.id === e.target.value);
+                  if (selectedExample) loadExample(selectedExample);
                 }}
                 value=""
               >
                 <option value="">Examples</option>
-                {examples.map(([name, _], idx) => (
-                  <option key={`${name}-${idx}`} value={name}>{name}</option>
+                {examples.map((example) => (
+                  <option key={example.id} value={example.id} title={example.description}>
+                    {example.title}
+                  </option>
                 ))}
               </select>
             </div>